@@ -1,7 +1,7 @@
 use warp::{
     filters::{body::BodyDeserializeError, cors::CorsForbidden},
     http::StatusCode,
-    reject::{Reject, UnsupportedMediaType},
+    reject::{InvalidQuery, Reject, UnsupportedMediaType},
     Rejection, Reply,
 };
 
@@ -23,6 +23,19 @@ pub enum Error {
     UnsupportedMediaType,
     PasswordInvalid,
     WrongEmailType,
+    TimerAlreadyPaused,
+    TimerNotPaused,
+    TimerNotFound,
+    InvalidTimeRange,
+    OverlappingTimeEntry,
+    InvalidPomodoro,
+    InvalidRecurrence,
+    InvalidTimeZone,
+    InvalidHierarchy,
+    InvalidStatusTransition,
+    InvalidCursor,
+    IrreversibleRevision,
+    InvalidPriority,
 }
 
 impl std::fmt::Display for Error {
@@ -56,6 +69,45 @@ impl std::fmt::Display for Error {
             Error::WrongEmailType => {
                 write!(f, "Email not correct")
             }
+            Error::TimerAlreadyPaused => {
+                write!(f, "Timer already paused")
+            }
+            Error::TimerNotPaused => {
+                write!(f, "Timer is not paused")
+            }
+            Error::TimerNotFound => {
+                write!(f, "Timer not found")
+            }
+            Error::InvalidTimeRange => {
+                write!(f, "Time range should end after start")
+            }
+            Error::OverlappingTimeEntry => {
+                write!(f, "Time entry overlaps another entry")
+            }
+            Error::InvalidPomodoro => {
+                write!(f, "Pomodoro lengths are out of range")
+            }
+            Error::InvalidRecurrence => {
+                write!(f, "Recurrence rule is invalid")
+            }
+            Error::InvalidTimeZone => {
+                write!(f, "Unknown time zone")
+            }
+            Error::InvalidHierarchy => {
+                write!(f, "Project or parent activity is invalid")
+            }
+            Error::InvalidStatusTransition => {
+                write!(f, "Status transition is not allowed")
+            }
+            Error::InvalidCursor => {
+                write!(f, "Cursor is invalid or belongs to another order")
+            }
+            Error::IrreversibleRevision => {
+                write!(f, "Revision can't be reverted")
+            }
+            Error::InvalidPriority => {
+                write!(f, "Priority is out of range")
+            }
         }
     }
 }
@@ -80,6 +132,12 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
             error.to_string(),
             StatusCode::UNPROCESSABLE_ENTITY,
         ))
+    } else if let Some(error) = r.find::<InvalidQuery>() {
+        event!(Level::ERROR, "Wrong query parameters");
+        Ok(warp::reply::with_status(
+            error.to_string(),
+            StatusCode::BAD_REQUEST,
+        ))
     } else if let Some(crate::Error::PasswordInvalid) = r.find() {
         Ok(warp::reply::with_status(
             "Password not meet criteria".to_string(),
@@ -114,6 +172,90 @@ pub async fn return_error(r: Rejection) -> Result<impl Reply, Rejection> {
             "Unprocessable entity".to_string(),
             StatusCode::UNPROCESSABLE_ENTITY,
         ))
+    } else if let Some(crate::Error::TimerAlreadyPaused) = r.find() {
+        event!(Level::ERROR, "Timer already paused");
+        Ok(warp::reply::with_status(
+            "Timer already paused".to_string(),
+            StatusCode::CONFLICT,
+        ))
+    } else if let Some(crate::Error::TimerNotPaused) = r.find() {
+        event!(Level::ERROR, "Timer is not paused");
+        Ok(warp::reply::with_status(
+            "Timer is not paused".to_string(),
+            StatusCode::CONFLICT,
+        ))
+    } else if let Some(crate::Error::TimerNotFound) = r.find() {
+        event!(Level::ERROR, "Timer not found");
+        Ok(warp::reply::with_status(
+            "Timer not found".to_string(),
+            StatusCode::NOT_FOUND,
+        ))
+    } else if let Some(crate::Error::TimeSpentNotFound) = r.find() {
+        event!(Level::ERROR, "Time entry not found");
+        Ok(warp::reply::with_status(
+            "Time spent not found".to_string(),
+            StatusCode::NOT_FOUND,
+        ))
+    } else if let Some(crate::Error::InvalidTimeRange) = r.find() {
+        event!(Level::ERROR, "Invalid time range");
+        Ok(warp::reply::with_status(
+            "Time range should end after start".to_string(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ))
+    } else if let Some(crate::Error::OverlappingTimeEntry) = r.find() {
+        event!(Level::ERROR, "Overlapping time entry");
+        Ok(warp::reply::with_status(
+            "Time entry overlaps another entry".to_string(),
+            StatusCode::CONFLICT,
+        ))
+    } else if let Some(crate::Error::InvalidPomodoro) = r.find() {
+        event!(Level::ERROR, "Invalid pomodoro settings");
+        Ok(warp::reply::with_status(
+            "Pomodoro lengths are out of range".to_string(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ))
+    } else if let Some(crate::Error::InvalidRecurrence) = r.find() {
+        event!(Level::ERROR, "Invalid recurrence rule");
+        Ok(warp::reply::with_status(
+            "Recurrence rule is invalid".to_string(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ))
+    } else if let Some(crate::Error::InvalidTimeZone) = r.find() {
+        event!(Level::ERROR, "Unknown time zone");
+        Ok(warp::reply::with_status(
+            "Unknown time zone".to_string(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ))
+    } else if let Some(crate::Error::InvalidHierarchy) = r.find() {
+        event!(Level::ERROR, "Project or parent activity is invalid");
+        Ok(warp::reply::with_status(
+            "Project or parent activity is invalid".to_string(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ))
+    } else if let Some(crate::Error::InvalidStatusTransition) = r.find() {
+        event!(Level::ERROR, "Status transition is not allowed");
+        Ok(warp::reply::with_status(
+            "Status transition is not allowed".to_string(),
+            StatusCode::CONFLICT,
+        ))
+    } else if let Some(crate::Error::InvalidCursor) = r.find() {
+        event!(Level::ERROR, "Invalid cursor");
+        Ok(warp::reply::with_status(
+            "Cursor is invalid or belongs to another order".to_string(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ))
+    } else if let Some(crate::Error::IrreversibleRevision) = r.find() {
+        event!(Level::ERROR, "Revision can't be reverted");
+        Ok(warp::reply::with_status(
+            "Revision can't be reverted".to_string(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ))
+    } else if let Some(crate::Error::InvalidPriority) = r.find() {
+        event!(Level::ERROR, "Invalid priority");
+        Ok(warp::reply::with_status(
+            "Priority is out of range".to_string(),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        ))
     } else if let Some(error) = r.find::<UnsupportedMediaType>() {
         event!(Level::ERROR, "Wrong body format");
         Ok(warp::reply::with_status(
@@ -180,4 +322,82 @@ mod handle_error_tests {
         println!("{answer:?}");
         assert_eq!(answer.status(), 406);
     }
+    #[tokio::test]
+    async fn small_test_timer_already_paused() {
+        let error_code = warp::reject::custom(Error::TimerAlreadyPaused);
+        let answer = return_error(error_code).await.unwrap().into_response();
+        assert_eq!(answer.status(), 409);
+    }
+    #[tokio::test]
+    async fn small_test_timer_not_paused() {
+        let error_code = warp::reject::custom(Error::TimerNotPaused);
+        let answer = return_error(error_code).await.unwrap().into_response();
+        assert_eq!(answer.status(), 409);
+    }
+    #[tokio::test]
+    async fn small_test_timer_not_found() {
+        let error_code = warp::reject::custom(Error::TimerNotFound);
+        let answer = return_error(error_code).await.unwrap().into_response();
+        assert_eq!(answer.status(), 404);
+    }
+    #[tokio::test]
+    async fn small_test_invalid_time_range() {
+        let error_code = warp::reject::custom(Error::InvalidTimeRange);
+        let answer = return_error(error_code).await.unwrap().into_response();
+        assert_eq!(answer.status(), 422);
+    }
+    #[tokio::test]
+    async fn small_test_overlapping_time_entry() {
+        let error_code = warp::reject::custom(Error::OverlappingTimeEntry);
+        let answer = return_error(error_code).await.unwrap().into_response();
+        assert_eq!(answer.status(), 409);
+    }
+    #[tokio::test]
+    async fn small_test_invalid_pomodoro() {
+        let error_code = warp::reject::custom(Error::InvalidPomodoro);
+        let answer = return_error(error_code).await.unwrap().into_response();
+        assert_eq!(answer.status(), 422);
+    }
+    #[tokio::test]
+    async fn small_test_invalid_recurrence() {
+        let error_code = warp::reject::custom(Error::InvalidRecurrence);
+        let answer = return_error(error_code).await.unwrap().into_response();
+        assert_eq!(answer.status(), 422);
+    }
+    #[tokio::test]
+    async fn small_test_invalid_time_zone() {
+        let error_code = warp::reject::custom(Error::InvalidTimeZone);
+        let answer = return_error(error_code).await.unwrap().into_response();
+        assert_eq!(answer.status(), 422);
+    }
+    #[tokio::test]
+    async fn small_test_invalid_hierarchy() {
+        let error_code = warp::reject::custom(Error::InvalidHierarchy);
+        let answer = return_error(error_code).await.unwrap().into_response();
+        assert_eq!(answer.status(), 422);
+    }
+    #[tokio::test]
+    async fn small_test_invalid_status_transition() {
+        let error_code = warp::reject::custom(Error::InvalidStatusTransition);
+        let answer = return_error(error_code).await.unwrap().into_response();
+        assert_eq!(answer.status(), 409);
+    }
+    #[tokio::test]
+    async fn small_test_invalid_cursor() {
+        let error_code = warp::reject::custom(Error::InvalidCursor);
+        let answer = return_error(error_code).await.unwrap().into_response();
+        assert_eq!(answer.status(), 422);
+    }
+    #[tokio::test]
+    async fn small_test_irreversible_revision() {
+        let error_code = warp::reject::custom(Error::IrreversibleRevision);
+        let answer = return_error(error_code).await.unwrap().into_response();
+        assert_eq!(answer.status(), 422);
+    }
+    #[tokio::test]
+    async fn small_test_invalid_priority() {
+        let error_code = warp::reject::custom(Error::InvalidPriority);
+        let answer = return_error(error_code).await.unwrap().into_response();
+        assert_eq!(answer.status(), 422);
+    }
 }
@@ -0,0 +1,11 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS time_entries (
+    id serial PRIMARY KEY,
+    activity_id integer NOT NULL REFERENCES activities (id) ON DELETE CASCADE,
+    account_id integer NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    stopped_at TIMESTAMPTZ NOT NULL,
+    duration integer NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS time_entries_activity_id_idx ON time_entries (activity_id);
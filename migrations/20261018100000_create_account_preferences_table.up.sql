@@ -0,0 +1,5 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS account_preferences (
+    account_id integer PRIMARY KEY,
+    single_active_timer BOOLEAN NOT NULL DEFAULT FALSE
+);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+UPDATE activities SET time = time - spent;
+
+ALTER TABLE activities
+DROP COLUMN spent;
@@ -0,0 +1,13 @@
+-- Add up migration script here
+ALTER TABLE activities
+ADD COLUMN spent integer NOT NULL DEFAULT 0;
+
+-- time was decreased by every booked entry, give the estimate back
+UPDATE activities
+SET spent = entries.total, time = activities.time + entries.total
+FROM (
+    SELECT activity_id, SUM(duration)::integer AS total
+    FROM time_entries
+    GROUP BY activity_id
+) AS entries
+WHERE activities.id = entries.activity_id;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+ALTER TABLE time_entries
+DROP COLUMN needs_review;
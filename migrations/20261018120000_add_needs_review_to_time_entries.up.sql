@@ -0,0 +1,3 @@
+-- Add up migration script here
+ALTER TABLE time_entries
+ADD COLUMN needs_review BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,14 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS activity_recurrences (
+    activity_id integer PRIMARY KEY REFERENCES activities (id) ON DELETE CASCADE,
+    account_id integer NOT NULL,
+    frequency VARCHAR (16) NOT NULL,
+    repeat_interval integer NOT NULL DEFAULT 1,
+    by_weekday smallint[] NOT NULL DEFAULT '{}',
+    starts_at TIMESTAMPTZ NOT NULL,
+    until TIMESTAMPTZ,
+    count integer,
+    exceptions DATE[] NOT NULL DEFAULT '{}'
+);
+
+CREATE INDEX IF NOT EXISTS activity_recurrences_account_id_idx ON activity_recurrences (account_id);
@@ -0,0 +1,7 @@
+-- Add down migration script here
+ALTER TABLE activities
+DROP COLUMN IF EXISTS due_at,
+DROP COLUMN IF EXISTS scheduled_end,
+DROP COLUMN IF EXISTS scheduled_start,
+DROP COLUMN IF EXISTS updated_on,
+ALTER COLUMN created_on TYPE TIMESTAMP USING created_on AT TIME ZONE 'UTC';
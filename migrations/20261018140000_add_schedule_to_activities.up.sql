@@ -0,0 +1,9 @@
+-- Add up migration script here
+ALTER TABLE activities
+ALTER COLUMN created_on TYPE TIMESTAMPTZ USING created_on AT TIME ZONE 'UTC',
+ADD COLUMN updated_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+ADD COLUMN scheduled_start TIMESTAMPTZ,
+ADD COLUMN scheduled_end TIMESTAMPTZ,
+ADD COLUMN due_at TIMESTAMPTZ;
+
+UPDATE activities SET updated_on = created_on;
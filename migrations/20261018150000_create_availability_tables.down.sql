@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS time_off;
+DROP TABLE IF EXISTS availability_rules;
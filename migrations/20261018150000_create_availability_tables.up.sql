@@ -0,0 +1,21 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS availability_rules (
+    id serial PRIMARY KEY,
+    account_id integer NOT NULL,
+    weekday smallint NOT NULL,
+    kind VARCHAR (16) NOT NULL,
+    start_time TIME NOT NULL,
+    end_time TIME NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS availability_rules_account_id_idx ON availability_rules (account_id);
+
+CREATE TABLE IF NOT EXISTS time_off (
+    id serial PRIMARY KEY,
+    account_id integer NOT NULL,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    reason TEXT
+);
+
+CREATE INDEX IF NOT EXISTS time_off_account_id_idx ON time_off (account_id);
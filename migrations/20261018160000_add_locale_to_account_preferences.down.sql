@@ -0,0 +1,6 @@
+-- Add down migration script here
+ALTER TABLE account_preferences
+DROP COLUMN time_format,
+DROP COLUMN default_activity_duration,
+DROP COLUMN week_start,
+DROP COLUMN timezone;
@@ -0,0 +1,6 @@
+-- Add up migration script here
+ALTER TABLE account_preferences
+ADD COLUMN timezone VARCHAR (64) NOT NULL DEFAULT 'UTC',
+ADD COLUMN week_start smallint NOT NULL DEFAULT 0,
+ADD COLUMN default_activity_duration integer NOT NULL DEFAULT 30,
+ADD COLUMN time_format VARCHAR (8) NOT NULL DEFAULT '24h';
@@ -0,0 +1,16 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS tags (
+    id serial PRIMARY KEY,
+    account_id integer NOT NULL,
+    name VARCHAR (64) NOT NULL,
+    color VARCHAR (16),
+    UNIQUE (account_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS activity_tags (
+    activity_id integer NOT NULL REFERENCES activities (id) ON DELETE CASCADE,
+    tag_id integer NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+    PRIMARY KEY (activity_id, tag_id)
+);
+
+CREATE INDEX IF NOT EXISTS activity_tags_tag_id_idx ON activity_tags (tag_id);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+ALTER TABLE activities
+DROP COLUMN parent_id,
+DROP COLUMN project_id;
+
+DROP TABLE IF EXISTS projects;
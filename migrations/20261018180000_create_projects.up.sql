@@ -0,0 +1,17 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS projects (
+    id serial PRIMARY KEY,
+    account_id integer NOT NULL,
+    title VARCHAR (255) NOT NULL,
+    content TEXT NOT NULL DEFAULT '',
+    created_on TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS projects_account_id_idx ON projects (account_id);
+
+ALTER TABLE activities
+ADD COLUMN project_id integer REFERENCES projects (id) ON DELETE SET NULL,
+ADD COLUMN parent_id integer REFERENCES activities (id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS activities_project_id_idx ON activities (project_id);
+CREATE INDEX IF NOT EXISTS activities_parent_id_idx ON activities (parent_id);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP TABLE IF EXISTS activity_status_history;
+
+ALTER TABLE activities
+DROP COLUMN status_changed_on,
+DROP COLUMN status;
@@ -0,0 +1,14 @@
+-- Add up migration script here
+ALTER TABLE activities
+ADD COLUMN status VARCHAR (16) NOT NULL DEFAULT 'todo',
+ADD COLUMN status_changed_on TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE TABLE IF NOT EXISTS activity_status_history (
+    id serial PRIMARY KEY,
+    activity_id integer NOT NULL REFERENCES activities (id) ON DELETE CASCADE,
+    from_status VARCHAR (16),
+    to_status VARCHAR (16) NOT NULL,
+    changed_on TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS activity_status_history_activity_id_idx ON activity_status_history (activity_id);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS activities_account_id_rank_idx;
+
+ALTER TABLE activities
+DROP COLUMN rank,
+DROP COLUMN priority;
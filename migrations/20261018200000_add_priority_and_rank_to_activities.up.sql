@@ -0,0 +1,14 @@
+-- Add up migration script here
+ALTER TABLE activities
+ADD COLUMN priority smallint NOT NULL DEFAULT 0,
+ADD COLUMN rank TEXT COLLATE "C" NOT NULL DEFAULT 'i';
+
+-- keep the current order of existing activities, ranks can't end with 0
+UPDATE activities SET rank = ranked.rank
+FROM (
+    SELECT id, rtrim('i' || lpad(row_number() OVER (PARTITION BY account_id ORDER BY id)::text, 7, '0'), '0') AS rank
+    FROM activities
+) ranked
+WHERE activities.id = ranked.id;
+
+CREATE INDEX IF NOT EXISTS activities_account_id_rank_idx ON activities (account_id, rank);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS activities_search_idx;
+ALTER TABLE activities DROP COLUMN IF EXISTS search;
@@ -0,0 +1,8 @@
+-- Add up migration script here
+ALTER TABLE activities
+    ADD COLUMN IF NOT EXISTS search tsvector GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', title), 'A') ||
+        setweight(to_tsvector('simple', content), 'B')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS activities_search_idx ON activities USING GIN (search);
@@ -0,0 +1,3 @@
+-- Add down migration script here
+DROP INDEX IF EXISTS activities_deleted_at_idx;
+ALTER TABLE activities DROP COLUMN IF EXISTS deleted_at;
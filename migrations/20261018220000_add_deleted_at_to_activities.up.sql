@@ -0,0 +1,5 @@
+-- Add up migration script here
+ALTER TABLE activities ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS activities_deleted_at_idx ON activities (deleted_at)
+    WHERE deleted_at IS NOT NULL;
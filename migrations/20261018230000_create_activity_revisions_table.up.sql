@@ -0,0 +1,13 @@
+-- Add up migration script here
+CREATE TABLE IF NOT EXISTS activity_revisions (
+    id serial PRIMARY KEY,
+    activity_id integer NOT NULL REFERENCES activities (id) ON DELETE CASCADE,
+    rev integer NOT NULL,
+    kind VARCHAR (16) NOT NULL,
+    changed_by integer NOT NULL,
+    changed_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    reverts integer,
+    before JSONB NOT NULL,
+    after JSONB NOT NULL,
+    UNIQUE (activity_id, rev)
+);
@@ -1,10 +1,93 @@
-use chrono::prelude::*;
+use std::fmt;
+use std::str::FromStr;
 use tracing::info;
 
 extern crate redis;
-use redis::Commands;
+use redis::{Commands, ErrorKind};
 use redis_pool::{RedisPool, SingleRedisPool};
 
+use crate::types::account::AccountID;
+use crate::types::timer::TimerState;
+
+const KEY_NAMESPACE: &str = "scheduler";
+
+/// What the cached value is used for, every purpose has own key space
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyPurpose {
+    Timer,
+    RunningTimers,
+}
+
+/// Structured redis key, scoped by account and optionally by activity
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheKey {
+    pub account_id: AccountID,
+    pub activity_id: Option<i32>,
+    pub purpose: KeyPurpose,
+}
+
+impl CacheKey {
+    pub fn new(account_id: &AccountID, purpose: KeyPurpose) -> Self {
+        CacheKey {
+            account_id: account_id.clone(),
+            activity_id: None,
+            purpose,
+        }
+    }
+
+    pub fn activity(mut self, activity_id: i32) -> Self {
+        self.activity_id = Some(activity_id);
+        self
+    }
+
+    pub fn timer(account_id: &AccountID, activity_id: i32) -> Self {
+        CacheKey::new(account_id, KeyPurpose::Timer).activity(activity_id)
+    }
+
+    pub fn running_timers(account_id: &AccountID) -> Self {
+        CacheKey::new(account_id, KeyPurpose::RunningTimers)
+    }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:account:{}", KEY_NAMESPACE, self.account_id.0)?;
+        if let Some(activity_id) = self.activity_id {
+            write!(f, ":activity:{}", activity_id)?;
+        }
+        match self.purpose {
+            KeyPurpose::Timer => write!(f, ":timer"),
+            KeyPurpose::RunningTimers => write!(f, ":timers"),
+        }
+    }
+}
+
+impl FromStr for CacheKey {
+    type Err = String;
+
+    fn from_str(key: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = key.split(':').collect();
+        let account_id = |value: &str| {
+            value
+                .parse::<i32>()
+                .map(AccountID)
+                .map_err(|_| format!("Wrong account in cache key {}", key))
+        };
+        match parts.as_slice() {
+            [KEY_NAMESPACE, "account", account, "timers"] => {
+                Ok(CacheKey::running_timers(&account_id(account)?))
+            }
+            [KEY_NAMESPACE, "account", account, "activity", activity, "timer"] => {
+                let activity_id = activity
+                    .parse::<i32>()
+                    .map_err(|_| format!("Wrong activity in cache key {}", key))?;
+                Ok(CacheKey::timer(&account_id(account)?, activity_id))
+            }
+            _ => Err(format!("Unknown cache key {}", key)),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct CacheStore {
     pub pool: SingleRedisPool,
@@ -18,22 +101,161 @@ impl CacheStore {
         Ok(CacheStore { pool })
     }
 
-    pub async fn set_value(
+    pub async fn set_timer(
         &mut self,
-        key_name: String,
-        value: DateTime<Utc>,
+        key: &CacheKey,
+        value: &TimerState,
     ) -> Result<(), redis::RedisError> {
-        self.pool
-            .get_connection()
-            .unwrap()
-            .set(key_name, value.timestamp_nanos_opt().unwrap())
+        let value = serde_json::to_string(value).map_err(|e| {
+            redis::RedisError::from((
+                ErrorKind::TypeError,
+                "Cannot serialize timer",
+                e.to_string(),
+            ))
+        })?;
+        redis::pipe()
+            .atomic()
+            .set(key.to_string(), value)
+            .ignore()
+            .sadd(
+                CacheKey::running_timers(&key.account_id).to_string(),
+                key.activity_id,
+            )
+            .ignore()
+            .query(&mut self.pool.get_connection().unwrap())
+    }
+
+    /// Overwrites a timer which is still there, false when it was stopped in between
+    pub async fn update_timer(
+        &mut self,
+        key: &CacheKey,
+        value: &TimerState,
+    ) -> Result<bool, redis::RedisError> {
+        let value = serde_json::to_string(value).map_err(|e| {
+            redis::RedisError::from((
+                ErrorKind::TypeError,
+                "Cannot serialize timer",
+                e.to_string(),
+            ))
+        })?;
+        let updated: Option<String> = redis::cmd("SET")
+            .arg(key.to_string())
+            .arg(value)
+            .arg("XX")
+            .query(&mut self.pool.get_connection().unwrap())?;
+        Ok(updated.is_some())
+    }
+
+    pub async fn get_timer(&mut self, key: &CacheKey) -> Result<TimerState, redis::RedisError> {
+        let value: String = self.pool.get_connection().unwrap().get(key.to_string())?;
+        serde_json::from_str(&value).map_err(|e| {
+            redis::RedisError::from((ErrorKind::TypeError, "Cannot parse timer", e.to_string()))
+        })
+    }
+
+    /// Removes the timer and returns it, of concurrent callers only one gets the timer
+    pub async fn take_timer(&mut self, key: &CacheKey) -> Result<TimerState, redis::RedisError> {
+        let (value,): (Option<String>,) = redis::pipe()
+            .atomic()
+            .get(key.to_string())
+            .del(key.to_string())
+            .ignore()
+            .srem(
+                CacheKey::running_timers(&key.account_id).to_string(),
+                key.activity_id,
+            )
+            .ignore()
+            .query(&mut self.pool.get_connection().unwrap())?;
+        let value = value
+            .ok_or_else(|| redis::RedisError::from((ErrorKind::TypeError, "Timer not found")))?;
+        serde_json::from_str(&value).map_err(|e| {
+            redis::RedisError::from((ErrorKind::TypeError, "Cannot parse timer", e.to_string()))
+        })
+    }
+
+    /// Timers of the account by activity id, keys left without timer are dropped from index
+    pub async fn get_running_timers(
+        &mut self,
+        account_id: &AccountID,
+    ) -> Result<Vec<(i32, TimerState)>, redis::RedisError> {
+        let index = CacheKey::running_timers(account_id).to_string();
+        let activity_ids: Vec<i32> = self.pool.get_connection().unwrap().smembers(&index)?;
+        if activity_ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut pipe = redis::pipe();
+        for activity_id in &activity_ids {
+            pipe.get(CacheKey::timer(account_id, *activity_id).to_string());
+        }
+        let values: Vec<Option<String>> = pipe.query(&mut self.pool.get_connection().unwrap())?;
+
+        let mut timers = vec![];
+        for (activity_id, value) in activity_ids.into_iter().zip(values) {
+            match value.and_then(|value| serde_json::from_str(&value).ok()) {
+                Some(timer) => timers.push((activity_id, timer)),
+                None => self
+                    .pool
+                    .get_connection()
+                    .unwrap()
+                    .srem(&index, activity_id)?,
+            }
+        }
+        Ok(timers)
+    }
+
+    /// Accounts which have at least one timer in the index
+    pub async fn get_accounts_with_timers(&mut self) -> Result<Vec<AccountID>, redis::RedisError> {
+        let pattern = format!("{}:account:*:timers", KEY_NAMESPACE);
+        let mut connection = self.pool.get_connection().unwrap();
+        let keys: Vec<String> = connection.scan_match(pattern)?.collect();
+        Ok(keys
+            .iter()
+            .filter_map(|key| key.parse::<CacheKey>().ok())
+            .map(|key| key.account_id)
+            .collect())
+    }
+
+    pub async fn delete_timer(&mut self, key: &CacheKey) -> Result<(), redis::RedisError> {
+        redis::pipe()
+            .atomic()
+            .del(key.to_string())
+            .ignore()
+            .srem(
+                CacheKey::running_timers(&key.account_id).to_string(),
+                key.activity_id,
+            )
+            .ignore()
+            .query(&mut self.pool.get_connection().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod test_cache_keys {
+    use super::*;
+
+    #[test]
+    fn small_test_timer_key_is_scoped_by_account_and_activity() {
+        let key = CacheKey::timer(&AccountID(3), 7);
+        assert_eq!(key.to_string(), "scheduler:account:3:activity:7:timer");
     }
 
-    pub async fn get_value(&mut self, key_name: String) -> Result<i64, redis::RedisError> {
-        self.pool.get_connection().unwrap().get(key_name)
+    #[test]
+    fn small_test_running_timers_key_is_scoped_by_account() {
+        let key = CacheKey::running_timers(&AccountID(3));
+        assert_eq!(key.to_string(), "scheduler:account:3:timers");
+        assert_ne!(
+            CacheKey::running_timers(&AccountID(3)),
+            CacheKey::running_timers(&AccountID(4))
+        );
     }
 
-    pub async fn delete_value(&mut self, key_name: String) -> Result<(), redis::RedisError> {
-        self.pool.get_connection().unwrap().del(key_name)
+    #[test]
+    fn small_test_cache_key_can_be_parsed_back() {
+        let key = CacheKey::timer(&AccountID(3), 7);
+        assert_eq!(key.to_string().parse::<CacheKey>(), Ok(key));
+        let key = CacheKey::running_timers(&AccountID(3));
+        assert_eq!(key.to_string().parse::<CacheKey>(), Ok(key));
+        assert!("7".parse::<CacheKey>().is_err());
     }
 }
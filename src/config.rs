@@ -32,6 +32,21 @@ pub struct Config {
     /// cache port
     #[clap(long, default_value = "6379")]
     pub cache_port: u16,
+    /// Timers running longer are stopped by the sweeper, in seconds
+    #[clap(long, default_value = "28800")]
+    pub timer_max_duration: i64,
+    /// How often the sweeper looks for abandoned timers, in seconds
+    #[clap(long, default_value = "60")]
+    pub timer_sweep_interval: u64,
+    /// Sweeper also stops timers which used up the activity budget
+    #[clap(long)]
+    pub stop_timers_over_budget: bool,
+    /// Deleted activities are kept in the trash for so many days
+    #[clap(long, default_value = "30")]
+    pub trash_retention_days: i64,
+    /// How often the trash is purged, in seconds
+    #[clap(long, default_value = "3600")]
+    pub trash_purge_interval: u64,
 }
 
 impl Config {
@@ -55,6 +70,17 @@ impl Config {
         let db_name = env::var("DATABASE_DB").unwrap_or(config.database_name.to_owned());
         let cache_host = env::var("CACHE_HOST").unwrap();
         let cache_port = env::var("CACHE_PORT").unwrap_or(config.cache_port.to_string());
+        let timer_max_duration =
+            env::var("TIMER_MAX_DURATION").unwrap_or(config.timer_max_duration.to_string());
+        let timer_sweep_interval =
+            env::var("TIMER_SWEEP_INTERVAL").unwrap_or(config.timer_sweep_interval.to_string());
+        let stop_timers_over_budget = env::var("STOP_TIMERS_OVER_BUDGET")
+            .map(|val| val == "true")
+            .unwrap_or(config.stop_timers_over_budget);
+        let trash_retention_days =
+            env::var("TRASH_RETENTION_DAYS").unwrap_or(config.trash_retention_days.to_string());
+        let trash_purge_interval =
+            env::var("TRASH_PURGE_INTERVAL").unwrap_or(config.trash_purge_interval.to_string());
         Ok(Config {
             log_level: config.log_level,
             port,
@@ -69,6 +95,19 @@ impl Config {
             cache_port: cache_port
                 .parse::<u16>()
                 .map_err(handle_errors::Error::ParseError)?,
+            timer_max_duration: timer_max_duration
+                .parse::<i64>()
+                .map_err(handle_errors::Error::ParseError)?,
+            timer_sweep_interval: timer_sweep_interval
+                .parse::<u64>()
+                .map_err(handle_errors::Error::ParseError)?,
+            stop_timers_over_budget,
+            trash_retention_days: trash_retention_days
+                .parse::<i64>()
+                .map_err(handle_errors::Error::ParseError)?,
+            trash_purge_interval: trash_purge_interval
+                .parse::<u64>()
+                .map_err(handle_errors::Error::ParseError)?,
         })
     }
 }
@@ -100,6 +139,11 @@ mod config_tests {
             database_name: "userdb".to_string(),
             cache_host: "localhost".to_string(),
             cache_port: 6379,
+            timer_max_duration: 28800,
+            timer_sweep_interval: 60,
+            stop_timers_over_budget: false,
+            trash_retention_days: 30,
+            trash_purge_interval: 3600,
         };
         let config = Config::new().unwrap();
         assert_eq!(config, expexted);
@@ -0,0 +1,98 @@
+use tokio::sync::broadcast;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tracing::info;
+
+use crate::types::account::AccountID;
+use crate::types::events::{AccountEvent, Event};
+
+/// In-process fan out of account changes to the open event streams
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<AccountEvent>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        EventBus { sender }
+    }
+
+    /// Sends the event to the subscribers of the account, nobody listening is fine
+    pub fn publish(&self, account_id: &AccountID, event: Event) {
+        let _ = self.sender.send(AccountEvent {
+            account_id: account_id.clone(),
+            event,
+        });
+    }
+
+    /// Events of one account, a subscriber that lags behind gets a resync event
+    /// in place of the missed events
+    pub fn subscribe(&self, account_id: AccountID) -> impl Stream<Item = Event> {
+        BroadcastStream::new(self.sender.subscribe()).filter_map(move |message| match message {
+            Ok(message) if message.account_id == account_id => Some(message.event),
+            Ok(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                info!("event stream of {:?} lagged by {}", account_id, missed);
+                Some(Event::Resync { missed })
+            }
+        })
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        EventBus::new(256)
+    }
+}
+
+#[cfg(test)]
+mod test_event_bus {
+    use super::*;
+    use crate::types::activities::ActivityId;
+
+    #[tokio::test]
+    async fn small_test_subscriber_gets_only_own_account_events() {
+        let bus = EventBus::default();
+        let mut stream = Box::pin(bus.subscribe(AccountID(1)));
+        bus.publish(
+            &AccountID(2),
+            Event::ActivityDeleted {
+                activity_id: ActivityId(2),
+            },
+        );
+        bus.publish(
+            &AccountID(1),
+            Event::ActivityDeleted {
+                activity_id: ActivityId(1),
+            },
+        );
+        match stream.next().await {
+            Some(Event::ActivityDeleted { activity_id }) => assert_eq!(activity_id, ActivityId(1)),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn small_test_lagging_subscriber_gets_resync() {
+        let bus = EventBus::new(1);
+        let mut stream = Box::pin(bus.subscribe(AccountID(1)));
+        for id in 1..=3 {
+            bus.publish(
+                &AccountID(1),
+                Event::ActivityDeleted {
+                    activity_id: ActivityId(id),
+                },
+            );
+        }
+        match stream.next().await {
+            Some(Event::Resync { missed }) => assert_eq!(missed, 2),
+            other => panic!("unexpected event {:?}", other),
+        }
+        match stream.next().await {
+            Some(Event::ActivityDeleted { activity_id }) => assert_eq!(activity_id, ActivityId(3)),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,433 @@
+use std::time::Duration;
+
+use chrono::prelude::*;
+use chrono::TimeDelta;
+use tracing::{error, info};
+
+use crate::cache::{CacheKey, CacheStore};
+use crate::config::Config;
+use crate::events::EventBus;
+use crate::routes::timer::book_timer;
+use crate::store::Store;
+use crate::types::account::AccountID;
+use crate::types::entries::TimeEntry;
+use crate::types::timer::TimerState;
+
+/// Stops timers running longer than `max_duration` seconds, with `over_budget` also
+/// the ones which used up the activity budget. Only the allowed part is booked.
+/// Paused timers started more than `max_duration` seconds ago are abandoned and stopped too,
+/// as are timers of activities moved to the trash. Timers of removed activities are dropped.
+pub async fn sweep_timers(
+    store: Store,
+    cache: CacheStore,
+    events: EventBus,
+    max_duration: i64,
+    over_budget: bool,
+) -> Result<Vec<TimeEntry>, redis::RedisError> {
+    let mut stopped = vec![];
+
+    for account_id in cache.clone().get_accounts_with_timers().await? {
+        let running = cache.clone().get_running_timers(&account_id).await?;
+        stopped.extend(
+            sweep_account_timers(
+                &account_id,
+                running,
+                store.clone(),
+                cache.clone(),
+                events.clone(),
+                max_duration,
+                over_budget,
+            )
+            .await?,
+        );
+    }
+    Ok(stopped)
+}
+
+/// Stops the `running` timers of the account which are over the limits. The timers
+/// are only read, a timer stopped or paused meanwhile is never written back.
+async fn sweep_account_timers(
+    account_id: &AccountID,
+    running: Vec<(i32, TimerState)>,
+    store: Store,
+    cache: CacheStore,
+    events: EventBus,
+    max_duration: i64,
+    over_budget: bool,
+) -> Result<Vec<TimeEntry>, redis::RedisError> {
+    let time = Utc::now();
+    let mut stopped = vec![];
+
+    for (activity_id, timer) in running {
+        let limit = match store
+            .clone()
+            .get_activity_by_id(account_id.clone(), activity_id)
+            .await
+        {
+            Ok(activity) => {
+                let limit = if over_budget {
+                    max_duration.min(activity.remaining as i64)
+                } else {
+                    max_duration
+                };
+                let abandoned =
+                    timer.is_paused() && (time - timer.started_at).num_seconds() > max_duration;
+                if timer.worked(time) <= limit && !abandoned {
+                    continue;
+                }
+                limit
+            }
+            Err(_) => match store.is_trashed(activity_id, account_id).await {
+                // the time tracked before the delete still counts
+                Ok(true) => max_duration,
+                Ok(false) => {
+                    info!("drop timer of missing activity {}", activity_id);
+                    cache
+                        .clone()
+                        .delete_timer(&CacheKey::timer(account_id, activity_id))
+                        .await?;
+                    continue;
+                }
+                Err(e) => {
+                    error!("Can't check activity {} with {:?}", activity_id, e);
+                    continue;
+                }
+            },
+        };
+
+        match book_timer(
+            account_id,
+            activity_id,
+            time,
+            Some(limit),
+            store.clone(),
+            cache.clone(),
+            events.clone(),
+        )
+        .await
+        {
+            Ok(time_entry) => stopped.push(time_entry),
+            Err(e) => error!("Can't stop timer of {} with {:?}", activity_id, e),
+        }
+    }
+    Ok(stopped)
+}
+
+pub fn spawn_timer_sweeper(
+    config: &Config,
+    store: Store,
+    cache: CacheStore,
+    events: EventBus,
+) -> tokio::task::JoinHandle<()> {
+    let max_duration = config.timer_max_duration;
+    let over_budget = config.stop_timers_over_budget;
+    let period = Duration::from_secs(config.timer_sweep_interval.max(1));
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            match sweep_timers(
+                store.clone(),
+                cache.clone(),
+                events.clone(),
+                max_duration,
+                over_budget,
+            )
+            .await
+            {
+                Ok(stopped) if !stopped.is_empty() => {
+                    info!("sweeper stopped {} abandoned timers", stopped.len())
+                }
+                Ok(_) => {}
+                Err(e) => error!("Can't sweep timers with {:?}", e),
+            }
+        }
+    })
+}
+
+/// Removes for good the activities deleted more than `retention_days` ago
+pub async fn purge_trash(store: &Store, retention_days: i64) -> Result<u64, handle_errors::Error> {
+    store
+        .purge_trash(Utc::now() - TimeDelta::days(retention_days))
+        .await
+}
+
+pub fn spawn_trash_purger(config: &Config, store: Store) -> tokio::task::JoinHandle<()> {
+    let retention_days = config.trash_retention_days.max(0);
+    let period = Duration::from_secs(config.trash_purge_interval.max(1));
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        loop {
+            interval.tick().await;
+            match purge_trash(&store, retention_days).await {
+                Ok(purged) if purged > 0 => {
+                    info!("purger removed {} activities from the trash", purged)
+                }
+                Ok(_) => {}
+                Err(e) => error!("Can't purge trash with {:?}", e),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod test_jobs {
+    use crate::events::EventBus;
+    use crate::jobs::{purge_trash, sweep_account_timers, sweep_timers};
+    use crate::routes::timer::{pause, start, stop};
+    use crate::tests::helpers::{
+        create_postgres, create_redis, get_session, prepare_cache, prepare_store,
+    };
+    use crate::types::account::AccountID;
+    use crate::types::revisions::RevisionKind;
+    use crate::types::timer::TimerOptions;
+    use testcontainers::clients::Cli;
+
+    #[tokio::test]
+    async fn medium_test_sweeper_stops_timer_longer_than_max_duration() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+        let stopped = sweep_timers(store.clone(), cache.clone(), EventBus::default(), 1, false)
+            .await
+            .unwrap();
+        assert_eq!(stopped.len(), 1);
+        assert_eq!(stopped[0].duration, 1);
+        assert!(stopped[0].needs_review);
+        let running = cache
+            .clone()
+            .get_running_timers(&AccountID(1))
+            .await
+            .unwrap();
+        assert!(running.is_empty());
+    }
+
+    #[tokio::test]
+    async fn medium_test_sweeper_stops_abandoned_paused_timer() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+        pause(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+        )
+        .await
+        .unwrap();
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+        let stopped = sweep_timers(store.clone(), cache.clone(), EventBus::default(), 1, false)
+            .await
+            .unwrap();
+        assert_eq!(stopped.len(), 1);
+        assert!(!stopped[0].needs_review);
+        let running = cache
+            .clone()
+            .get_running_timers(&AccountID(1))
+            .await
+            .unwrap();
+        assert!(running.is_empty());
+    }
+
+    #[tokio::test]
+    async fn medium_test_sweeper_doesnt_recreate_timer_stopped_meanwhile() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        // the sweeper read the timers, then the user stops it
+        let running = cache
+            .clone()
+            .get_running_timers(&AccountID(1))
+            .await
+            .unwrap();
+        stop(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+        )
+        .await
+        .unwrap();
+
+        for max_duration in [3600, 1] {
+            let stopped = sweep_account_timers(
+                &AccountID(1),
+                running.clone(),
+                store.clone(),
+                cache.clone(),
+                EventBus::default(),
+                max_duration,
+                false,
+            )
+            .await
+            .unwrap();
+            assert!(stopped.is_empty());
+        }
+        let running = cache
+            .clone()
+            .get_running_timers(&AccountID(1))
+            .await
+            .unwrap();
+        assert!(running.is_empty());
+        let entries = store.get_time_entries(AccountID(1), 1).await.unwrap();
+        assert_eq!(entries.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn medium_test_sweeper_keeps_timers_in_limits() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+
+        let stopped = sweep_timers(
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            3600,
+            true,
+        )
+        .await
+        .unwrap();
+        assert!(stopped.is_empty());
+    }
+
+    #[tokio::test]
+    async fn medium_test_sweeper_books_timer_of_trashed_activity() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        store
+            .clone()
+            .delete_activity(1, AccountID(1))
+            .await
+            .unwrap();
+
+        let stopped = sweep_timers(
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            3600,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(stopped.len(), 1);
+        let entries = store
+            .clone()
+            .get_time_entries(AccountID(1), 1)
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        let revisions = store.clone().get_revisions(1, AccountID(1)).await.unwrap();
+        assert_eq!(revisions.last().unwrap().kind, RevisionKind::TimerStopped);
+        let running = cache
+            .clone()
+            .get_running_timers(&AccountID(1))
+            .await
+            .unwrap();
+        assert!(running.is_empty());
+    }
+
+    #[tokio::test]
+    async fn medium_test_purger_removes_only_expired_trash() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        for _ in 0..3 {
+            store.clone().add_test_acctivities().await;
+        }
+        for id in [1, 2] {
+            store.delete_activity(id, AccountID(1)).await.unwrap();
+        }
+        sqlx::query("UPDATE activities SET deleted_at = NOW() - INTERVAL '31 days' WHERE id = 1")
+            .execute(&store.connection)
+            .await
+            .unwrap();
+
+        assert_eq!(purge_trash(&store, 30).await.unwrap(), 1);
+        let trash = store.clone().get_trash(AccountID(1)).await.unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].activity.id.0, 2);
+        assert!(store.get_activity_by_id(AccountID(1), 3).await.is_ok());
+    }
+}
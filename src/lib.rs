@@ -13,7 +13,10 @@ use utoipa_swagger_ui::Config as SwaggerConfig;
 pub use handle_errors;
 pub mod cache;
 pub mod config;
+pub mod events;
+pub mod jobs;
 pub mod routes;
+pub mod schedule;
 pub mod store;
 pub mod swagger;
 pub mod tests;
@@ -24,13 +27,16 @@ const VERSION: &str = "v1";
 async fn build_routes(
     store: store::Store,
     cache: cache::CacheStore,
+    events: events::EventBus,
 ) -> impl Filter<Extract = impl Reply, Error = Rejection> + Clone {
     let store_filter = warp::any().map(move || store.clone());
     let cache_filter = warp::any().map(move || cache.clone());
+    let events_filter = warp::any().map(move || events.clone());
 
     let cors = warp::cors()
         .allow_any_origin()
         .allow_header("content-type")
+        .expose_headers(vec!["x-total-count", "link"])
         .allow_methods(&[Method::PUT, Method::DELETE, Method::GET, Method::POST]);
 
     let health_check = warp::get()
@@ -45,9 +51,23 @@ async fn build_routes(
         .and(warp::path::end())
         .and(routes::authentication::auth())
         .and(warp::query::<types::pagination::Pagination>())
+        .and(warp::query::<types::activities::ActivityFilter>())
+        .and(warp::path::full())
+        .and(warp::query::raw().or(warp::any().map(String::new)).unify())
         .and(store_filter.clone())
         .and_then(routes::activities::get_activities);
 
+    let search_activities = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path("search"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(warp::query::<types::search::SearchQuery>())
+        .and(warp::query::<types::pagination::Pagination>())
+        .and(store_filter.clone())
+        .and_then(routes::activities::search_activities);
+
     let get_activity_by_id = warp::get()
         .and(warp::path(VERSION))
         .and(warp::path("activity"))
@@ -63,6 +83,7 @@ async fn build_routes(
         .and(warp::path::end())
         .and(routes::authentication::auth())
         .and(store_filter.clone())
+        .and(events_filter.clone())
         .and(warp::body::json())
         .and_then(routes::activities::add_activity);
 
@@ -73,9 +94,202 @@ async fn build_routes(
         .and(warp::path::end())
         .and(routes::authentication::auth())
         .and(store_filter.clone())
+        .and(events_filter.clone())
         .and(warp::body::json())
         .and_then(routes::activities::update_activities);
 
+    let move_activity = warp::post()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("move"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(events_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::activities::move_activity);
+
+    let set_status = warp::put()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("status"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(events_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::activities::set_status);
+
+    let get_status_history = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("status"))
+        .and(warp::path("history"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::activities::get_status_history);
+
+    let get_history = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("history"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::activities::get_history);
+
+    let revert_activity = warp::post()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("revert"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(events_filter.clone())
+        .and_then(routes::activities::revert_activity);
+
+    let get_entries = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("entries"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::entries::get_entries);
+
+    let get_recurrence = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("recurrence"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::recurrence::get_recurrence);
+
+    let set_recurrence = warp::put()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("recurrence"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::recurrence::set_recurrence);
+
+    let delete_recurrence = warp::delete()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("recurrence"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::recurrence::delete_recurrence);
+
+    let get_occurrences = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("occurrences"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(warp::query::<types::recurrence::OccurrenceRange>())
+        .and(store_filter.clone())
+        .and_then(routes::recurrence::get_occurrences);
+
+    let get_freebusy = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("freebusy"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(warp::query::<types::recurrence::OccurrenceRange>())
+        .and(store_filter.clone())
+        .and_then(routes::freebusy::get_freebusy);
+
+    let plan_day = warp::post()
+        .and(warp::path(VERSION))
+        .and(warp::path("plan"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(warp::query::<types::plan::PlanQuery>())
+        .and(store_filter.clone())
+        .and_then(routes::plan::plan_day);
+
+    let accept_plan = warp::post()
+        .and(warp::path(VERSION))
+        .and(warp::path("plan"))
+        .and(warp::path("accept"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(events_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::plan::accept_plan);
+
+    let list_timers = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("timer"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(cache_filter.clone())
+        .and_then(routes::timer::list);
+
+    let timer_status = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("timer"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(cache_filter.clone())
+        .and_then(routes::timer::status);
+
+    let add_entry = warp::post()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("entries"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(events_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::entries::add_entry);
+
+    let update_entry = warp::put()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("entries"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(events_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::entries::update_entry);
+
+    let delete_entry = warp::delete()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("entries"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(events_filter.clone())
+        .and_then(routes::entries::delete_entry);
+
     let start_timer = warp::post()
         .and(warp::path(VERSION))
         .and(warp::path("timer"))
@@ -85,8 +299,34 @@ async fn build_routes(
         .and(routes::authentication::auth())
         .and(store_filter.clone())
         .and(cache_filter.clone())
+        .and(events_filter.clone())
+        .and(warp::query())
         .and_then(routes::timer::start);
 
+    let pause_timer = warp::post()
+        .and(warp::path(VERSION))
+        .and(warp::path("timer"))
+        .and(warp::path("pause"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(cache_filter.clone())
+        .and(events_filter.clone())
+        .and_then(routes::timer::pause);
+
+    let resume_timer = warp::post()
+        .and(warp::path(VERSION))
+        .and(warp::path("timer"))
+        .and(warp::path("resume"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(cache_filter.clone())
+        .and(events_filter.clone())
+        .and_then(routes::timer::resume);
+
     let stop_timer = warp::post()
         .and(warp::path(VERSION))
         .and(warp::path("timer"))
@@ -96,6 +336,7 @@ async fn build_routes(
         .and(routes::authentication::auth())
         .and(store_filter.clone())
         .and(cache_filter.clone())
+        .and(events_filter.clone())
         .and_then(routes::timer::stop);
 
     let deleted_activities = warp::delete()
@@ -105,8 +346,252 @@ async fn build_routes(
         .and(warp::path::end())
         .and(routes::authentication::auth())
         .and(store_filter.clone())
+        .and(cache_filter.clone())
+        .and(events_filter.clone())
         .and_then(routes::activities::deleted_activities);
 
+    let get_trash = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("trash"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::trash::get_trash);
+
+    let restore_activity = warp::post()
+        .and(warp::path(VERSION))
+        .and(warp::path("trash"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("restore"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(events_filter.clone())
+        .and_then(routes::trash::restore_activity);
+
+    let events = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("events"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(events_filter.clone())
+        .and_then(routes::events::events);
+
+    let get_preferences = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("account"))
+        .and(warp::path("preferences"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::preferences::get_preferences);
+
+    let update_preferences = warp::put()
+        .and(warp::path(VERSION))
+        .and(warp::path("account"))
+        .and(warp::path("preferences"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::preferences::update_preferences);
+
+    let get_availability = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("account"))
+        .and(warp::path("availability"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::availability::get_availability);
+
+    let add_availability_rule = warp::post()
+        .and(warp::path(VERSION))
+        .and(warp::path("account"))
+        .and(warp::path("availability"))
+        .and(warp::path("rules"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::availability::add_rule);
+
+    let update_availability_rule = warp::put()
+        .and(warp::path(VERSION))
+        .and(warp::path("account"))
+        .and(warp::path("availability"))
+        .and(warp::path("rules"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::availability::update_rule);
+
+    let delete_availability_rule = warp::delete()
+        .and(warp::path(VERSION))
+        .and(warp::path("account"))
+        .and(warp::path("availability"))
+        .and(warp::path("rules"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::availability::delete_rule);
+
+    let add_time_off = warp::post()
+        .and(warp::path(VERSION))
+        .and(warp::path("account"))
+        .and(warp::path("time_off"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::availability::add_time_off);
+
+    let update_time_off = warp::put()
+        .and(warp::path(VERSION))
+        .and(warp::path("account"))
+        .and(warp::path("time_off"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::availability::update_time_off);
+
+    let delete_time_off = warp::delete()
+        .and(warp::path(VERSION))
+        .and(warp::path("account"))
+        .and(warp::path("time_off"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::availability::delete_time_off);
+
+    let get_tags = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("tags"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::tags::get_tags);
+
+    let add_tag = warp::post()
+        .and(warp::path(VERSION))
+        .and(warp::path("tags"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::tags::add_tag);
+
+    let update_tag = warp::put()
+        .and(warp::path(VERSION))
+        .and(warp::path("tags"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::tags::update_tag);
+
+    let delete_tag = warp::delete()
+        .and(warp::path(VERSION))
+        .and(warp::path("tags"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::tags::delete_tag);
+
+    let get_activity_tags = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("tags"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::tags::get_activity_tags);
+
+    let set_activity_tags = warp::put()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("tags"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::tags::set_activity_tags);
+
+    let get_projects = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("project"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::projects::get_projects);
+
+    let get_project = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("project"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::projects::get_project);
+
+    let add_project = warp::post()
+        .and(warp::path(VERSION))
+        .and(warp::path("project"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::projects::add_project);
+
+    let update_project = warp::put()
+        .and(warp::path(VERSION))
+        .and(warp::path("project"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and(warp::body::json())
+        .and_then(routes::projects::update_project);
+
+    let delete_project = warp::delete()
+        .and(warp::path(VERSION))
+        .and(warp::path("project"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::projects::delete_project);
+
+    let get_project_activities = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("project"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("activities"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::projects::get_project_activities);
+
+    let get_activity_tree = warp::get()
+        .and(warp::path(VERSION))
+        .and(warp::path("activity"))
+        .and(warp::path::param::<i32>())
+        .and(warp::path("tree"))
+        .and(warp::path::end())
+        .and(routes::authentication::auth())
+        .and(store_filter.clone())
+        .and_then(routes::projects::get_activity_tree);
+
     let registration = warp::post()
         .and(warp::path(VERSION))
         .and(warp::path("registration"))
@@ -123,16 +608,78 @@ async fn build_routes(
         .and(warp::body::json())
         .and_then(routes::authentication::login);
 
-    get_activities
+    // grouped and boxed to keep the filter type within the compiler limits
+    let activity_routes = get_activities
+        .or(search_activities)
         .or(get_activity_by_id)
-        .or(health_check)
         .or(add_activity)
         .or(update_activities)
         .or(deleted_activities)
+        .or(get_trash)
+        .or(restore_activity)
+        .or(move_activity)
+        .or(set_status)
+        .or(get_status_history)
+        .or(get_history)
+        .or(revert_activity)
+        .or(get_entries)
+        .or(add_entry)
+        .or(update_entry)
+        .or(delete_entry)
+        .or(get_recurrence)
+        .or(set_recurrence)
+        .or(delete_recurrence)
+        .or(get_occurrences)
+        .or(plan_day)
+        .or(accept_plan)
+        .or(get_freebusy)
+        .boxed();
+
+    let timer_routes = list_timers
+        .or(timer_status)
         .or(start_timer)
+        .or(pause_timer)
+        .or(resume_timer)
         .or(stop_timer)
+        .or(events)
+        .boxed();
+
+    let account_routes = get_preferences
+        .or(update_preferences)
+        .or(get_availability)
+        .or(add_availability_rule)
+        .or(update_availability_rule)
+        .or(delete_availability_rule)
+        .or(add_time_off)
+        .or(update_time_off)
+        .or(delete_time_off)
         .or(registration)
         .or(login)
+        .boxed();
+
+    let tag_routes = get_tags
+        .or(add_tag)
+        .or(update_tag)
+        .or(delete_tag)
+        .or(get_activity_tags)
+        .or(set_activity_tags)
+        .boxed();
+
+    let project_routes = get_projects
+        .or(get_project)
+        .or(add_project)
+        .or(update_project)
+        .or(delete_project)
+        .or(get_project_activities)
+        .or(get_activity_tree)
+        .boxed();
+
+    health_check
+        .or(activity_routes)
+        .or(timer_routes)
+        .or(account_routes)
+        .or(tag_routes)
+        .or(project_routes)
         .with(cors)
         .with(warp::trace::request())
         .recover(handle_errors::return_error)
@@ -209,7 +756,11 @@ pub async fn run(config: config::Config, store: store::Store, cache: cache::Cach
         .and(warp::any().map(move || swagger_config.clone()))
         .and_then(serve_swagger);
 
-    let routes = build_routes(store, cache).await;
+    let events = events::EventBus::default();
+    jobs::spawn_timer_sweeper(&config, store.clone(), cache.clone(), events.clone());
+    jobs::spawn_trash_purger(&config, store.clone());
+
+    let routes = build_routes(store, cache, events).await;
 
     warp::serve(api_doc.or(swagger_ui).or(routes))
         .run(([0, 0, 0, 0], config.port))
@@ -226,6 +777,7 @@ mod test_scheduler {
     use crate::{
         build_routes,
         config::Config,
+        events::EventBus,
         setup_store,
         tests::helpers::{
             convert_to_string, create_postgres, create_redis, prepare_cache, prepare_store,
@@ -252,6 +804,11 @@ mod test_scheduler {
             database_name: "postgres".to_string(),
             cache_port: 6379,
             cache_host: "localhost".to_string(),
+            timer_max_duration: 28800,
+            timer_sweep_interval: 60,
+            stop_timers_over_budget: false,
+            trash_retention_days: 30,
+            trash_purge_interval: 3600,
         };
         let result = setup_store(&config).await;
         assert!(result.is_ok())
@@ -266,7 +823,7 @@ mod test_scheduler {
         let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
         let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
 
-        let filter = build_routes(store, cache).await;
+        let filter = build_routes(store, cache, EventBus::default()).await;
 
         let register = format!("/{}/registration", VERSION);
         let login = format!("/{}/login", VERSION);
@@ -312,7 +869,7 @@ mod test_scheduler {
         let raw_act = convert_to_string(add_req.body()).await.unwrap();
         let new_act: NewActivity = serde_json::from_str(&raw_act).unwrap();
 
-        assert_eq!(new_act.time, 3600);
+        assert_eq!(new_act.time, Some(3600));
         assert_eq!(new_act.title, "awesome title");
         assert_eq!(new_act.content, "awesome content");
         assert_eq!(add_req.status(), 201);
@@ -1,22 +1,43 @@
 use std::collections::HashMap;
 
+use crate::cache::CacheStore;
+use crate::events::EventBus;
+use crate::routes::timer::book_timer;
 use crate::store::Store;
-use crate::types::account::Session;
-use crate::types::activities::{Activity, ActivityId, NewActivity, PartiaActivity};
-use crate::types::pagination::Pagination;
+use crate::types::account::{AccountID, Session};
+use crate::types::activities::{
+    check_priority, check_schedule, Activity, ActivityFilter, ActivityId, MoveActivity,
+    NewActivity, NewStatus, PartiaActivity, StatusChange,
+};
+use crate::types::events::Event;
+use crate::types::pagination::{link_header, Cursor, Direction, Pagination, DEFAULT_LIMIT};
+use crate::types::projects::ProjectId;
+use crate::types::revisions::Revision;
+use crate::types::search::{SearchHit, SearchQuery};
+use chrono::Utc;
 use tracing::{info, instrument};
+use warp::http::header::{HeaderValue, LINK};
 use warp::http::StatusCode;
+use warp::path::FullPath;
 use warp::reply::json;
+use warp::Reply;
 
 #[instrument]
 #[utoipa::path(
         get,
         path = "activity",
         responses(
-            (status = 200, description = "List activities", body = [Activity]),
-            (status = 404, description = "Rout not found")
+            (status = 200, description = "List activities", body = [Activity],
+                headers(
+                    ("X-Total-Count" = i64, description = "Number of activities matching the filters"),
+                    ("Link" = String, description = "Cursors of the next and previous pages")
+                )
+            ),
+            (status = 400, description = "Unknown filter or sort value"),
+            (status = 404, description = "Rout not found"),
+            (status = 422, description = "Deadline range ends before start, limit is out of range or cursor is invalid")
         ),
-        params(Pagination),
+        params(Pagination, ActivityFilter),
         security(
             ("Authorization" = [])
         )
@@ -24,11 +45,101 @@ use warp::reply::json;
 pub async fn get_activities(
     session: Session,
     params: Pagination,
+    filter: ActivityFilter,
+    path: FullPath,
+    query: String,
     store: Store,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     info!("quering activities");
-    let res: Vec<Activity> = match store
-        .get_activities(session.account_id, params.limit, params.offset)
+    let params = params.validate()?;
+    let filter = filter.validate()?;
+    let account_id = session.account_id;
+    let sort = filter.sort.unwrap_or_default();
+    let order = sort.resolve(filter.order);
+    let cursor = match params.cursor.as_deref() {
+        Some(cursor) => Some(Cursor::decode(cursor, sort, order)?),
+        None => None,
+    };
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+
+    let total = store.count_activities(account_id.clone(), &filter).await?;
+    // One more row tells if there is a page after this one
+    let mut res: Vec<Activity> = match store
+        .get_activities(
+            account_id,
+            Some(limit + 1),
+            params.offset,
+            cursor.as_ref(),
+            filter,
+        )
+        .await
+    {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    let backwards = cursor
+        .as_ref()
+        .is_some_and(|cursor| cursor.direction == Direction::Prev);
+    let has_more = res.len() > limit as usize;
+    if has_more && backwards {
+        res.remove(0);
+    } else if has_more {
+        res.pop();
+    }
+    let has_next = has_more || backwards;
+    let has_prev = if backwards {
+        has_more
+    } else {
+        cursor.is_some() || params.offset.unwrap_or(0) > 0
+    };
+    let next = res
+        .last()
+        .filter(|_| has_next)
+        .map(|activity| Cursor::new(Direction::Next, sort, order, activity));
+    let prev = res
+        .first()
+        .filter(|_| has_prev)
+        .map(|activity| Cursor::new(Direction::Prev, sort, order, activity));
+
+    let mut response = warp::reply::json(&res).into_response();
+    response
+        .headers_mut()
+        .insert("X-Total-Count", HeaderValue::from(total));
+    if let Some(link) = link_header(path.as_str(), &query, next.as_ref(), prev.as_ref()) {
+        if let Ok(link) = HeaderValue::from_str(&link) {
+            response.headers_mut().insert(LINK, link);
+        }
+    }
+    Ok(response)
+}
+
+#[instrument]
+#[utoipa::path(
+        get,
+        path = "activity/search",
+        responses(
+            (status = 200, description = "Activities matching the query, best first", body = [SearchHit]),
+            (status = 422, description = "query is empty or too long, limit is out of range or cursor is given")
+        ),
+        params(SearchQuery, Pagination),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn search_activities(
+    session: Session,
+    query: SearchQuery,
+    params: Pagination,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("searching activities");
+    let query = query.validate()?;
+    let params = params.validate()?;
+    if params.cursor.is_some() {
+        return Err(warp::reject::custom(handle_errors::Error::InvalidCursor));
+    }
+    let res: Vec<SearchHit> = match store
+        .search_activities(session.account_id, query.q, params.limit, params.offset)
         .await
     {
         Ok(res) => res,
@@ -74,7 +185,7 @@ pub async fn get_activity_by_id(
         responses(
             (status = 201, description = "activity added", body = Activity),
             (status = 409, description = "activity is already exists"),
-            (status = 422, description = "can't add activities", body = Activity)
+            (status = 422, description = "can't add activities, schedule ends before start, priority is out of range or project/parent is invalid", body = Activity)
         ),
         security(
             ("Authorization" = [])
@@ -83,17 +194,50 @@ pub async fn get_activity_by_id(
 pub async fn add_activity(
     session: Session,
     store: Store,
+    events: EventBus,
     mut new_activity: NewActivity,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     info!("add activity");
     let account_id = session.account_id;
-    new_activity.time = new_activity.time.wrapping_mul(60);
-    if let Err(e) = store.add_activity(new_activity.clone(), account_id).await {
-        info!("Activity not added{:?}", new_activity.clone());
-        return Err(warp::reject::custom(e));
-    }
+    let minutes = match new_activity.time {
+        Some(minutes) => minutes,
+        None => {
+            store
+                .clone()
+                .get_preferences(account_id.clone())
+                .await?
+                .default_activity_duration
+        }
+    };
+    new_activity.time = Some(minutes.wrapping_mul(60));
+    check_schedule(new_activity.scheduled_start, new_activity.scheduled_end)?;
+    check_priority(new_activity.priority)?;
+    new_activity.project_id = check_hierarchy(
+        &store,
+        &account_id,
+        None,
+        new_activity.project_id,
+        new_activity.parent_id.as_ref(),
+    )
+    .await?;
+    let activity = match store
+        .add_activity(new_activity.clone(), account_id.clone())
+        .await
+    {
+        Ok(activity) => activity,
+        Err(e) => {
+            info!("Activity not added{:?}", new_activity.clone());
+            return Err(warp::reject::custom(e));
+        }
+    };
+    events.publish(
+        &account_id,
+        Event::ActivityCreated {
+            activity: activity.clone(),
+        },
+    );
     Ok(warp::reply::with_status(
-        json(&new_activity),
+        json(&activity),
         StatusCode::CREATED,
     ))
 }
@@ -101,14 +245,14 @@ pub async fn add_activity(
 #[utoipa::path(
         put,
         path = "activity/{id}",
-        request_body = NewActivity,
+        request_body = PartiaActivity,
         params(
             ("id" = i32, Path, description = "Activity unique id")
         ),
         responses(
             (status = 201, description = "activity updated", body = Activity),
             (status = 404, description = "activity not found"),
-            (status = 422, description = "can't add activities", body = Activity)
+            (status = 422, description = "can't add activities, schedule ends before start, priority is out of range or project/parent is invalid", body = Activity)
         ),
         security(
             ("Authorization" = [])
@@ -118,6 +262,7 @@ pub async fn update_activities(
     id: i32,
     session: Session,
     store: Store,
+    events: EventBus,
     new_activity: PartiaActivity,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     info!("update activities");
@@ -134,36 +279,65 @@ pub async fn update_activities(
         .get_activity_by_id(account_id.clone(), id)
         .await?;
 
-    let title = if new_activity.title.is_some() {
-        new_activity.title.unwrap()
-    } else {
-        old_activity.title
-    };
+    let title = new_activity.title.unwrap_or(old_activity.title);
 
-    let content = if new_activity.content.is_some() {
-        new_activity.content.unwrap()
-    } else {
-        old_activity.content
-    };
+    let content = new_activity.content.unwrap_or(old_activity.content);
 
-    let time = if new_activity.time.is_some() {
-        new_activity.time.unwrap() * 60
-    } else {
-        old_activity.time
+    let time = match new_activity.time {
+        Some(time) => time * 60,
+        None => old_activity.time,
     };
 
+    let scheduled_start = new_activity
+        .scheduled_start
+        .unwrap_or(old_activity.scheduled_start);
+    let scheduled_end = new_activity
+        .scheduled_end
+        .unwrap_or(old_activity.scheduled_end);
+    let due_at = new_activity.due_at.unwrap_or(old_activity.due_at);
+    check_schedule(scheduled_start, scheduled_end)?;
+    check_priority(new_activity.priority)?;
+    let priority = new_activity.priority.unwrap_or(old_activity.priority);
+    let parent_id = new_activity
+        .parent_id
+        .unwrap_or(old_activity.parent_id.clone());
+    let project_id = check_hierarchy(
+        &store,
+        &account_id,
+        Some(id),
+        new_activity.project_id.unwrap_or(old_activity.project_id),
+        parent_id.as_ref(),
+    )
+    .await?;
+
     let activity = Activity {
         id: ActivityId(id),
         title,
         content,
         time,
+        scheduled_start,
+        scheduled_end,
+        due_at,
+        project_id,
+        parent_id,
+        priority,
+        ..old_activity
     };
 
-    let res = match store.update_activity(activity, id, account_id).await {
+    let res = match store
+        .update_activity(activity, id, account_id.clone(), None)
+        .await
+    {
         Ok(res) => res,
         Err(e) => return Err(warp::reject::custom(e)),
     };
     info!("Update completed with {:?}", &res);
+    events.publish(
+        &account_id,
+        Event::ActivityUpdated {
+            activity: res.clone(),
+        },
+    );
     Ok(warp::reply::with_status(json(&res), StatusCode::CREATED))
 }
 
@@ -174,7 +348,7 @@ pub async fn update_activities(
             ("id" = i32, Path, description = "Activity unique id")
         ),
         responses(
-            (status = 200, description = "activity deleted", body = i32),
+            (status = 200, description = "activity moved to the trash", body = i32),
             (status = 404, description = "activity not found"),
         ),
         security(
@@ -185,14 +359,37 @@ pub async fn deleted_activities(
     id: i32,
     session: Session,
     store: Store,
+    cache: CacheStore,
+    events: EventBus,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     info!("delete activities");
     let account_id = session.account_id;
 
     if store.is_activity_owner(id, &account_id).await? {
-        if let Err(e) = store.delete_activity(id, account_id).await {
+        // the running timer is booked while the activity can still take the time
+        match book_timer(
+            &account_id,
+            id,
+            Utc::now(),
+            None,
+            store.clone(),
+            cache,
+            events.clone(),
+        )
+        .await
+        {
+            Ok(_) | Err(handle_errors::Error::TimerNotFound) => {}
+            Err(e) => return Err(warp::reject::custom(e)),
+        }
+        if let Err(e) = store.delete_activity(id, account_id.clone()).await {
             return Err(warp::reject::custom(e));
         }
+        events.publish(
+            &account_id,
+            Event::ActivityDeleted {
+                activity_id: ActivityId(id),
+            },
+        );
 
         let answer = HashMap::from([("Activity deleted with id", id)]);
         Ok(warp::reply::with_status(json(&answer), StatusCode::OK))
@@ -204,15 +401,343 @@ pub async fn deleted_activities(
     }
 }
 
+#[utoipa::path(
+        post,
+        path = "activity/{id}/move",
+        request_body = MoveActivity,
+        params(
+            ("id" = i32, Path, description = "Activity unique id")
+        ),
+        responses(
+            (status = 201, description = "activity moved in the manual order", body = Activity),
+            (status = 404, description = "activity not found"),
+            (status = 422, description = "activity can't be moved after itself")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn move_activity(
+    id: i32,
+    session: Session,
+    store: Store,
+    events: EventBus,
+    new_place: MoveActivity,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("move activity");
+    let account_id = session.account_id;
+    let after = new_place.after.map(|after| after.0);
+    if after == Some(id) {
+        return Err(warp::reject::custom(
+            handle_errors::Error::MissingParameters,
+        ));
+    }
+
+    if !store.is_activity_owner(id, &account_id).await? {
+        return Ok(warp::reply::with_status(
+            json(&"Activity not found".to_string()),
+            StatusCode::NOT_FOUND,
+        ));
+    }
+    let res = match store.move_activity(id, after, account_id.clone()).await {
+        Ok(Some(res)) => res,
+        Ok(None) => {
+            return Ok(warp::reply::with_status(
+                json(&"Activity not found".to_string()),
+                StatusCode::NOT_FOUND,
+            ))
+        }
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    events.publish(
+        &account_id,
+        Event::ActivityUpdated {
+            activity: res.clone(),
+        },
+    );
+    Ok(warp::reply::with_status(json(&res), StatusCode::CREATED))
+}
+
+#[utoipa::path(
+        put,
+        path = "activity/{id}/status",
+        request_body = NewStatus,
+        params(
+            ("id" = i32, Path, description = "Activity unique id")
+        ),
+        responses(
+            (status = 201, description = "status changed", body = Activity),
+            (status = 404, description = "activity not found"),
+            (status = 409, description = "status transition is not allowed")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn set_status(
+    id: i32,
+    session: Session,
+    store: Store,
+    events: EventBus,
+    new_status: NewStatus,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("set activity status");
+    let account_id = session.account_id;
+
+    if !store.is_activity_owner(id, &account_id).await? {
+        return Ok(warp::reply::with_status(
+            json(&"Activity not found".to_string()),
+            StatusCode::NOT_FOUND,
+        ));
+    }
+    let activity = store
+        .clone()
+        .get_activity_by_id(account_id.clone(), id)
+        .await?;
+    let status = activity.status.transition(new_status.status)?;
+    let res = match store
+        .set_status(id, activity.status, status, account_id.clone())
+        .await
+    {
+        Ok(Some(res)) => res,
+        // changed by another request in between
+        Ok(None) => {
+            return Err(warp::reject::custom(
+                handle_errors::Error::InvalidStatusTransition,
+            ))
+        }
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    events.publish(
+        &account_id,
+        Event::ActivityUpdated {
+            activity: res.clone(),
+        },
+    );
+    Ok(warp::reply::with_status(json(&res), StatusCode::CREATED))
+}
+
+#[utoipa::path(
+        get,
+        path = "activity/{id}/status/history",
+        params(
+            ("id" = i32, Path, description = "Activity unique id")
+        ),
+        responses(
+            (status = 200, description = "Status changes of activity, the oldest first", body = [StatusChange]),
+            (status = 404, description = "activity not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_status_history(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering status history");
+    let account_id = session.account_id;
+
+    if !store.is_activity_owner(id, &account_id).await? {
+        return Ok(warp::reply::with_status(
+            json(&"Activity not found".to_string()),
+            StatusCode::NOT_FOUND,
+        ));
+    }
+    match store.get_status_history(id, account_id).await {
+        Ok(res) => Ok(warp::reply::with_status(json(&res), StatusCode::OK)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[utoipa::path(
+        get,
+        path = "activity/{id}/history",
+        params(
+            ("id" = i32, Path, description = "Activity unique id")
+        ),
+        responses(
+            (status = 200, description = "Changes of activity, the oldest first", body = [Revision]),
+            (status = 404, description = "activity not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_history(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering activity history");
+    let account_id = session.account_id;
+
+    if !store.is_activity_owner(id, &account_id).await? {
+        return Ok(warp::reply::with_status(
+            json(&"Activity not found".to_string()),
+            StatusCode::NOT_FOUND,
+        ));
+    }
+    match store.get_revisions(id, account_id).await {
+        Ok(res) => Ok(warp::reply::with_status(json(&res), StatusCode::OK)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[utoipa::path(
+        post,
+        path = "activity/{id}/revert/{rev}",
+        params(
+            ("id" = i32, Path, description = "Activity unique id"),
+            ("rev" = i32, Path, description = "Revision to undo")
+        ),
+        responses(
+            (status = 201, description = "fields changed by the revision are put back", body = Activity),
+            (status = 404, description = "activity or revision not found"),
+            (status = 422, description = "revision is not an edit or old project/parent is not valid anymore")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn revert_activity(
+    id: i32,
+    rev: i32,
+    session: Session,
+    store: Store,
+    events: EventBus,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("revert activity");
+    let account_id = session.account_id;
+
+    if !store.is_activity_owner(id, &account_id).await? {
+        return Ok(warp::reply::with_status(
+            json(&"Activity not found".to_string()),
+            StatusCode::NOT_FOUND,
+        ));
+    }
+    let Some(revision) = store.get_revision(id, rev, account_id.clone()).await? else {
+        return Ok(warp::reply::with_status(
+            json(&"Revision not found".to_string()),
+            StatusCode::NOT_FOUND,
+        ));
+    };
+    if !revision.can_revert() {
+        return Err(warp::reject::custom(
+            handle_errors::Error::IrreversibleRevision,
+        ));
+    }
+    let activity = store
+        .clone()
+        .get_activity_by_id(account_id.clone(), id)
+        .await?;
+    let activity = revision.before.undo(&revision.after, activity);
+    check_schedule(activity.scheduled_start, activity.scheduled_end)?;
+    let project_id = check_hierarchy(
+        &store,
+        &account_id,
+        Some(id),
+        activity.project_id,
+        activity.parent_id.as_ref(),
+    )
+    .await?;
+    let activity = Activity {
+        project_id,
+        ..activity
+    };
+
+    let res = match store
+        .update_activity(activity, id, account_id.clone(), Some(rev))
+        .await
+    {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    events.publish(
+        &account_id,
+        Event::ActivityUpdated {
+            activity: res.clone(),
+        },
+    );
+    Ok(warp::reply::with_status(json(&res), StatusCode::CREATED))
+}
+
+/// Project and parent should be owned by the account and the parent can't be
+/// the activity or its descendant. Returns the project, inherited from the parent when missing.
+async fn check_hierarchy(
+    store: &Store,
+    account_id: &AccountID,
+    id: Option<i32>,
+    project_id: Option<ProjectId>,
+    parent_id: Option<&ActivityId>,
+) -> Result<Option<ProjectId>, handle_errors::Error> {
+    if let Some(project_id) = project_id {
+        if store
+            .clone()
+            .get_project(project_id.0, account_id.clone())
+            .await?
+            .is_none()
+        {
+            return Err(handle_errors::Error::InvalidHierarchy);
+        }
+    }
+    let Some(parent_id) = parent_id else {
+        return Ok(project_id);
+    };
+    if !store.is_activity_owner(parent_id.0, account_id).await? {
+        return Err(handle_errors::Error::InvalidHierarchy);
+    }
+    if let Some(id) = id {
+        if store.is_ancestor(id, parent_id.0).await? {
+            return Err(handle_errors::Error::InvalidHierarchy);
+        }
+    }
+    match project_id {
+        Some(project_id) => Ok(Some(project_id)),
+        None => Ok(store
+            .clone()
+            .get_activity_by_id(account_id.clone(), parent_id.0)
+            .await?
+            .project_id),
+    }
+}
+
 #[cfg(test)]
 mod test_activities {
-    use crate::routes::activities::{add_activity, deleted_activities, update_activities};
-    use crate::tests::helpers::{create_postgres, get_session, prepare_store};
+    use crate::events::EventBus;
+    use crate::routes::activities::{
+        add_activity, deleted_activities, get_history, get_status_history, move_activity,
+        revert_activity, search_activities, set_status, update_activities,
+    };
+    use crate::routes::timer::start;
+    use crate::tests::helpers::{
+        convert_to_string, create_postgres, create_redis, get_session, prepare_cache, prepare_store,
+    };
     use crate::types::account::AccountID;
-    use crate::types::activities::{NewActivity, PartiaActivity};
+    use crate::types::activities::{
+        Activity, ActivityFilter, ActivityId, ActivitySort, ActivityStatus, MoveActivity,
+        NewActivity, NewStatus, PartiaActivity, SortOrder,
+    };
+    use crate::types::entries::NewTimeEntry;
+    use crate::types::events::Event;
+    use crate::types::pagination::{Cursor, Direction, Pagination};
+    use crate::types::revisions::RevisionKind;
+    use crate::types::search::{SearchHit, SearchQuery};
+    use crate::types::timer::TimerOptions;
+    use chrono::{DateTime, TimeDelta, Utc};
     use testcontainers_modules::testcontainers::clients::Cli;
+    use tokio_stream::StreamExt;
     use warp::reply::Reply;
 
+    fn no_pagination() -> Pagination {
+        Pagination {
+            limit: None,
+            offset: None,
+            cursor: None,
+        }
+    }
+
     #[tokio::test]
     async fn medium_test_add_activities() {
         let docker = Cli::default();
@@ -224,51 +749,617 @@ mod test_activities {
         let record = NewActivity {
             title: "test".to_string(),
             content: "test".to_string(),
-            time: 1,
+            time: Some(1),
+            scheduled_start: None,
+            scheduled_end: None,
+            due_at: None,
+            project_id: None,
+            parent_id: None,
+            priority: None,
         };
-        let result = add_activity(get_session(account_id), store.clone(), record)
-            .await
-            .unwrap()
-            .into_response();
+        let result = add_activity(
+            get_session(account_id),
+            store.clone(),
+            EventBus::default(),
+            record,
+        )
+        .await
+        .unwrap()
+        .into_response();
         assert_eq!(result.status(), 201);
     }
 
     #[tokio::test]
-    async fn medium_test_user_should_get_owned_activities_with_limit() {
+    async fn medium_test_user_can_finish_and_archive_activity() {
         let docker = Cli::default();
         let node = docker.run(create_postgres());
         let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
-        let account_id = 1;
-        let limit = 1;
-        store.clone().add_test_account(account_id).await;
+        store.clone().add_test_account(1).await;
         store.clone().add_test_acctivities().await;
-        store.clone().add_test_acctivities().await;
-        let result = store
+        for status in [ActivityStatus::Done, ActivityStatus::Archived] {
+            let result = set_status(
+                1,
+                get_session(1),
+                store.clone(),
+                EventBus::default(),
+                NewStatus { status },
+            )
+            .await
+            .unwrap()
+            .into_response();
+            assert_eq!(result.status(), 201);
+        }
+        let result = set_status(
+            1,
+            get_session(1),
+            store.clone(),
+            EventBus::default(),
+            NewStatus {
+                status: ActivityStatus::InProgress,
+            },
+        )
+        .await;
+        assert!(result.is_err());
+
+        let activity = store
             .clone()
-            .get_activities(AccountID(account_id), Some(limit), None)
+            .get_activity_by_id(AccountID(1), 1)
             .await
             .unwrap();
-        assert_eq!(result.len() as i32, limit);
+        assert_eq!(activity.status, ActivityStatus::Archived);
+        let history = store.get_status_history(1, AccountID(1)).await.unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0].from, None);
+        assert_eq!(history[2].from, Some(ActivityStatus::Done));
     }
 
     #[tokio::test]
-    async fn medium_test_user_should_get_owned_all_activities() {
+    async fn medium_test_user_cant_see_status_history_of_not_owned_activity() {
         let docker = Cli::default();
         let node = docker.run(create_postgres());
         let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
-        let account_id = 1;
-        let num_activities = 10;
-        store.clone().add_test_account(account_id).await;
-        for _ in 0..num_activities {
-            store.clone().add_test_acctivities().await;
-        }
-
-        let result = store
-            .clone()
-            .get_activities(AccountID(account_id), None, None)
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let result = get_status_history(1, get_session(2), store.clone())
             .await
-            .unwrap();
-        assert_eq!(result.len() as i32, num_activities);
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_can_revert_activity_to_earlier_revision() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        for time in [5, 10] {
+            let partial = PartiaActivity {
+                title: None,
+                content: None,
+                time: Some(time),
+                scheduled_start: None,
+                scheduled_end: None,
+                due_at: None,
+                project_id: None,
+                parent_id: None,
+                priority: None,
+            };
+            update_activities(
+                1,
+                get_session(1),
+                store.clone(),
+                EventBus::default(),
+                partial,
+            )
+            .await
+            .unwrap();
+        }
+        let now = Utc::now();
+        let entry = NewTimeEntry {
+            activity_id: ActivityId(1),
+            started_at: now - TimeDelta::minutes(1),
+            stopped_at: now,
+            duration: 60,
+            needs_review: false,
+        };
+        store
+            .clone()
+            .book_time_entry(entry, AccountID(1), RevisionKind::TimerStopped)
+            .await
+            .unwrap();
+
+        let result = revert_activity(1, 1, get_session(1), store.clone(), EventBus::default())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 201);
+        let result = revert_activity(1, 9, get_session(1), store.clone(), EventBus::default())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 404);
+
+        let history = store.clone().get_revisions(1, AccountID(1)).await.unwrap();
+        let kinds: Vec<RevisionKind> = history.iter().map(|revision| revision.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RevisionKind::Update,
+                RevisionKind::Update,
+                RevisionKind::TimerStopped,
+                RevisionKind::Revert
+            ]
+        );
+        assert_eq!(history[0].changes, vec!["time"]);
+        assert_eq!(history[0].after.time, 300);
+        assert_eq!(history[2].changes, vec!["spent"]);
+        assert_eq!(history[3].reverts, Some(1));
+        assert_eq!(history[3].changed_by, AccountID(1));
+
+        let activity = store
+            .clone()
+            .get_activity_by_id(AccountID(1), 1)
+            .await
+            .unwrap();
+        assert_eq!(activity.time, history[0].before.time);
+        assert_eq!(activity.spent, 60);
+
+        let result = get_history(1, get_session(2), store.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_cant_revert_timer_stopped_revision() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let now = Utc::now();
+        let entry = NewTimeEntry {
+            activity_id: ActivityId(1),
+            started_at: now - TimeDelta::minutes(1),
+            stopped_at: now,
+            duration: 60,
+            needs_review: false,
+        };
+        store
+            .clone()
+            .book_time_entry(entry, AccountID(1), RevisionKind::TimerStopped)
+            .await
+            .unwrap();
+
+        let result =
+            revert_activity(1, 1, get_session(1), store.clone(), EventBus::default()).await;
+        assert!(result.is_err());
+
+        let history = store.clone().get_revisions(1, AccountID(1)).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].kind, RevisionKind::TimerStopped);
+        let activity = store
+            .clone()
+            .get_activity_by_id(AccountID(1), 1)
+            .await
+            .unwrap();
+        assert_eq!(activity.spent, 60);
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_can_reorder_activities() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        for _ in 0..3 {
+            store.clone().add_test_acctivities().await;
+        }
+        let moves = [(3, None), (1, Some(ActivityId(3)))];
+        for (id, after) in moves {
+            let result = move_activity(
+                id,
+                get_session(1),
+                store.clone(),
+                EventBus::default(),
+                MoveActivity { after },
+            )
+            .await
+            .unwrap()
+            .into_response();
+            assert_eq!(result.status(), 201);
+        }
+        let result = store
+            .clone()
+            .get_activities(AccountID(1), None, None, None, ActivityFilter::default())
+            .await
+            .unwrap();
+        let order: Vec<i32> = result.iter().map(|activity| activity.id.0).collect();
+        assert_eq!(order, vec![3, 1, 2]);
+
+        let partial = PartiaActivity {
+            title: None,
+            content: None,
+            time: None,
+            scheduled_start: None,
+            scheduled_end: None,
+            due_at: None,
+            project_id: None,
+            parent_id: None,
+            priority: Some(4),
+        };
+        update_activities(
+            2,
+            get_session(1),
+            store.clone(),
+            EventBus::default(),
+            partial,
+        )
+        .await
+        .unwrap();
+        let filter = ActivityFilter {
+            sort: Some(ActivitySort::Priority),
+            ..Default::default()
+        };
+        let result = store
+            .clone()
+            .get_activities(AccountID(1), None, None, None, filter)
+            .await
+            .unwrap();
+        assert_eq!(result[0].id.0, 2);
+
+        let result = move_activity(
+            1,
+            get_session(2),
+            store.clone(),
+            EventBus::default(),
+            MoveActivity { after: None },
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(result.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn medium_test_concurrent_adds_and_moves_keep_ranks_unique() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        tokio::join!(
+            store.clone().add_test_acctivities(),
+            store.clone().add_test_acctivities(),
+            store.clone().add_test_acctivities(),
+            store.clone().add_test_acctivities(),
+        );
+        let place = || MoveActivity {
+            after: Some(ActivityId(1)),
+        };
+        let moves = tokio::join!(
+            move_activity(
+                2,
+                get_session(1),
+                store.clone(),
+                EventBus::default(),
+                place()
+            ),
+            move_activity(
+                3,
+                get_session(1),
+                store.clone(),
+                EventBus::default(),
+                place()
+            ),
+            move_activity(
+                4,
+                get_session(1),
+                store.clone(),
+                EventBus::default(),
+                place()
+            ),
+        );
+        for result in [moves.0, moves.1, moves.2] {
+            assert_eq!(result.unwrap().into_response().status(), 201);
+        }
+
+        let result = store
+            .clone()
+            .get_activities(AccountID(1), None, None, None, ActivityFilter::default())
+            .await
+            .unwrap();
+        let mut ranks: Vec<&str> = result
+            .iter()
+            .map(|activity| activity.rank.as_str())
+            .collect();
+        ranks.dedup();
+        assert_eq!(ranks.len(), 4);
+        assert_eq!(result[0].id.0, 1);
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_can_filter_and_sort_activities() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        for _ in 0..3 {
+            store.clone().add_test_acctivities().await;
+        }
+        let due_at: DateTime<Utc> = "2026-10-20T12:00:00Z".parse().unwrap();
+        for (id, days) in [(2, 0), (3, 1)] {
+            let partial = PartiaActivity {
+                title: Some(format!("Report {id}")),
+                content: None,
+                time: None,
+                scheduled_start: None,
+                scheduled_end: None,
+                due_at: Some(Some(due_at + TimeDelta::days(days))),
+                project_id: None,
+                parent_id: None,
+                priority: None,
+            };
+            update_activities(
+                id,
+                get_session(1),
+                store.clone(),
+                EventBus::default(),
+                partial,
+            )
+            .await
+            .unwrap();
+        }
+        set_status(
+            3,
+            get_session(1),
+            store.clone(),
+            EventBus::default(),
+            NewStatus {
+                status: ActivityStatus::Done,
+            },
+        )
+        .await
+        .unwrap();
+
+        let filter = ActivityFilter {
+            search: Some("report".to_string()),
+            sort: Some(ActivitySort::DueAt),
+            order: Some(SortOrder::Desc),
+            ..Default::default()
+        };
+        let result = store
+            .clone()
+            .get_activities(AccountID(1), None, None, None, filter)
+            .await
+            .unwrap();
+        let order: Vec<i32> = result.iter().map(|activity| activity.id.0).collect();
+        assert_eq!(order, vec![3, 2]);
+
+        let filter = ActivityFilter {
+            status: Some(ActivityStatus::Todo),
+            due_from: Some(due_at),
+            due_to: Some(due_at + TimeDelta::days(2)),
+            has_remaining: Some(true),
+            ..Default::default()
+        };
+        let result = store
+            .clone()
+            .get_activities(AccountID(1), None, None, None, filter)
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id.0, 2);
+
+        let filter = ActivityFilter {
+            search: Some("100%".to_string()),
+            ..Default::default()
+        };
+        let result = store
+            .get_activities(AccountID(1), None, None, None, filter)
+            .await
+            .unwrap();
+        assert!(result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_can_search_only_owned_activities() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        let records = [
+            (1, "Call finance", "ask about the quarterly report"),
+            (1, "Quarterly report", "collect the numbers"),
+            (2, "Quarterly report", "not mine"),
+        ];
+        for (account_id, title, content) in records {
+            let record = NewActivity {
+                title: title.to_string(),
+                content: content.to_string(),
+                time: Some(1),
+                scheduled_start: None,
+                scheduled_end: None,
+                due_at: None,
+                project_id: None,
+                parent_id: None,
+                priority: None,
+            };
+            store
+                .clone()
+                .add_activity(record, AccountID(account_id))
+                .await
+                .unwrap();
+        }
+        let query = SearchQuery {
+            q: "quarterly report".to_string(),
+        };
+        let result = search_activities(get_session(1), query, no_pagination(), store.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 200);
+        let body = warp::hyper::body::to_bytes(result.into_body())
+            .await
+            .unwrap();
+        let hits: Vec<SearchHit> =
+            serde_json::from_str(&convert_to_string(&body).await.unwrap()).unwrap();
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[0].activity.id.0, 2);
+        assert_eq!(hits[0].title, "<mark>Quarterly</mark> <mark>report</mark>");
+
+        let query = SearchQuery { q: " ".to_string() };
+        let result = search_activities(get_session(1), query, no_pagination(), store).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_can_walk_activities_with_cursors() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        for _ in 0..5 {
+            store.clone().add_test_acctivities().await;
+        }
+        let filter = || ActivityFilter {
+            sort: Some(ActivitySort::DueAt),
+            ..Default::default()
+        };
+        let page = |activities: &[Activity]| -> Vec<i32> {
+            activities.iter().map(|activity| activity.id.0).collect()
+        };
+        let first = store
+            .clone()
+            .get_activities(AccountID(1), Some(2), None, None, filter())
+            .await
+            .unwrap();
+        assert_eq!(page(&first), vec![1, 2]);
+
+        let next = Cursor::new(
+            Direction::Next,
+            ActivitySort::DueAt,
+            SortOrder::Asc,
+            &first[1],
+        );
+        let second = store
+            .clone()
+            .get_activities(AccountID(1), Some(2), None, Some(&next), filter())
+            .await
+            .unwrap();
+        assert_eq!(page(&second), vec![3, 4]);
+
+        let prev = Cursor::new(
+            Direction::Prev,
+            ActivitySort::DueAt,
+            SortOrder::Asc,
+            &second[0],
+        );
+        let back = store
+            .clone()
+            .get_activities(AccountID(1), Some(2), None, Some(&prev), filter())
+            .await
+            .unwrap();
+        assert_eq!(page(&back), vec![1, 2]);
+        assert_eq!(
+            store
+                .count_activities(AccountID(1), &filter())
+                .await
+                .unwrap(),
+            5
+        );
+    }
+
+    #[tokio::test]
+    async fn medium_test_activity_without_time_uses_default_duration() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let account_id = 1;
+        store.clone().add_test_account(account_id).await;
+
+        let record = NewActivity {
+            title: "test".to_string(),
+            content: "test".to_string(),
+            time: None,
+            scheduled_start: None,
+            scheduled_end: None,
+            due_at: None,
+            project_id: None,
+            parent_id: None,
+            priority: None,
+        };
+        add_activity(
+            get_session(account_id),
+            store.clone(),
+            EventBus::default(),
+            record,
+        )
+        .await
+        .unwrap();
+        let result = store
+            .get_activities(
+                AccountID(account_id),
+                None,
+                None,
+                None,
+                ActivityFilter::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result[0].time, 30 * 60);
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_should_get_owned_activities_with_limit() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let account_id = 1;
+        let limit = 1;
+        store.clone().add_test_account(account_id).await;
+        store.clone().add_test_acctivities().await;
+        store.clone().add_test_acctivities().await;
+        let result = store
+            .clone()
+            .get_activities(
+                AccountID(account_id),
+                Some(limit),
+                None,
+                None,
+                ActivityFilter::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.len() as i32, limit);
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_should_get_owned_all_activities() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let account_id = 1;
+        let num_activities = 10;
+        store.clone().add_test_account(account_id).await;
+        for _ in 0..num_activities {
+            store.clone().add_test_acctivities().await;
+        }
+
+        let result = store
+            .clone()
+            .get_activities(
+                AccountID(account_id),
+                None,
+                None,
+                None,
+                ActivityFilter::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.len() as i32, num_activities);
     }
 
     #[tokio::test]
@@ -285,7 +1376,13 @@ mod test_activities {
 
         let result = store
             .clone()
-            .get_activities(AccountID(account_id), None, Some(num_activities - 1))
+            .get_activities(
+                AccountID(account_id),
+                None,
+                Some(num_activities - 1),
+                None,
+                ActivityFilter::default(),
+            )
             .await
             .unwrap();
         assert_eq!(result.len() as i32, num_activities - (num_activities - 1));
@@ -304,11 +1401,23 @@ mod test_activities {
             title: Some("updated".to_string()),
             content: Some("full_update".to_string()),
             time: None,
+            scheduled_start: None,
+            scheduled_end: None,
+            due_at: None,
+            project_id: None,
+            parent_id: None,
+            priority: None,
         };
-        let result = update_activities(activity_id, get_session(account_id), store, for_update)
-            .await
-            .unwrap()
-            .into_response();
+        let result = update_activities(
+            activity_id,
+            get_session(account_id),
+            store,
+            EventBus::default(),
+            for_update,
+        )
+        .await
+        .unwrap()
+        .into_response();
         assert_eq!(result.status(), 201);
     }
     #[tokio::test]
@@ -325,11 +1434,23 @@ mod test_activities {
             title: Some("updated".to_string()),
             content: None,
             time: None,
+            scheduled_start: None,
+            scheduled_end: None,
+            due_at: None,
+            project_id: None,
+            parent_id: None,
+            priority: None,
         };
-        let result = update_activities(1, get_session(account_id), store, for_update)
-            .await
-            .unwrap()
-            .into_response();
+        let result = update_activities(
+            1,
+            get_session(account_id),
+            store,
+            EventBus::default(),
+            for_update,
+        )
+        .await
+        .unwrap()
+        .into_response();
         assert_eq!(result.status(), 404);
     }
 
@@ -337,27 +1458,206 @@ mod test_activities {
     async fn medium_test_user_should_delete_owned_activities() {
         let docker = Cli::default();
         let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
         let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
         let account_id = 1;
         store.clone().add_test_account(account_id).await;
         store.clone().add_test_acctivities().await;
-        let result = deleted_activities(1, get_session(account_id), store)
-            .await
-            .unwrap()
-            .into_response();
+        let result = deleted_activities(
+            1,
+            get_session(account_id),
+            store,
+            cache,
+            EventBus::default(),
+        )
+        .await
+        .unwrap()
+        .into_response();
         assert_eq!(result.status(), 200);
     }
+
+    #[tokio::test]
+    async fn medium_test_deleting_activity_books_running_timer() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+        let result = deleted_activities(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(result.status(), 200);
+
+        let entries = store
+            .clone()
+            .get_time_entries(AccountID(1), 1)
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        let running = cache
+            .clone()
+            .get_running_timers(&AccountID(1))
+            .await
+            .unwrap();
+        assert!(running.is_empty());
+    }
     #[tokio::test]
     async fn medium_test_user_should_not_delete_not_owned_activities() {
         let docker = Cli::default();
         let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
         let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
         let account_id = 1;
         store.clone().add_test_account(account_id).await;
-        let result = deleted_activities(1, get_session(account_id), store)
-            .await
-            .unwrap()
-            .into_response();
+        let result = deleted_activities(
+            1,
+            get_session(account_id),
+            store,
+            cache,
+            EventBus::default(),
+        )
+        .await
+        .unwrap()
+        .into_response();
         assert_eq!(result.status(), 404);
     }
+
+    #[tokio::test]
+    async fn medium_test_add_activity_publishes_event() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let account_id = 1;
+        store.clone().add_test_account(account_id).await;
+        let events = EventBus::default();
+        let mut stream = Box::pin(events.subscribe(AccountID(account_id)));
+
+        let record = NewActivity {
+            title: "test".to_string(),
+            content: "test".to_string(),
+            time: Some(1),
+            scheduled_start: None,
+            scheduled_end: None,
+            due_at: None,
+            project_id: None,
+            parent_id: None,
+            priority: None,
+        };
+        add_activity(get_session(account_id), store.clone(), events, record)
+            .await
+            .unwrap();
+        match stream.next().await {
+            Some(Event::ActivityCreated { activity }) => assert_eq!(activity.title, "test"),
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_can_schedule_and_clear_deadline() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let account_id = 1;
+        store.clone().add_test_account(account_id).await;
+        store.clone().add_test_acctivities().await;
+        let start: DateTime<Utc> = "2026-10-19T09:00:00Z".parse().unwrap();
+        let for_update = PartiaActivity {
+            title: None,
+            content: None,
+            time: None,
+            scheduled_start: Some(Some(start)),
+            scheduled_end: Some(Some(start + TimeDelta::hours(1))),
+            due_at: Some(Some(start + TimeDelta::days(1))),
+            project_id: None,
+            parent_id: None,
+            priority: None,
+        };
+        update_activities(
+            1,
+            get_session(account_id),
+            store.clone(),
+            EventBus::default(),
+            for_update,
+        )
+        .await
+        .unwrap();
+        let activity = store
+            .clone()
+            .get_activity_by_id(AccountID(account_id), 1)
+            .await
+            .unwrap();
+        assert_eq!(activity.scheduled_start, Some(start));
+        assert!(activity.due_at.is_some());
+
+        let for_update = PartiaActivity {
+            title: None,
+            content: None,
+            time: None,
+            scheduled_start: None,
+            scheduled_end: None,
+            due_at: Some(None),
+            project_id: None,
+            parent_id: None,
+            priority: None,
+        };
+        update_activities(
+            1,
+            get_session(account_id),
+            store.clone(),
+            EventBus::default(),
+            for_update,
+        )
+        .await
+        .unwrap();
+        let activity = store
+            .clone()
+            .get_activity_by_id(AccountID(account_id), 1)
+            .await
+            .unwrap();
+        assert_eq!(activity.scheduled_start, Some(start));
+        assert_eq!(activity.due_at, None);
+        assert!(activity.updated_on >= activity.created_on);
+
+        let for_update = PartiaActivity {
+            title: None,
+            content: None,
+            time: None,
+            scheduled_start: None,
+            scheduled_end: Some(Some(start)),
+            due_at: None,
+            project_id: None,
+            parent_id: None,
+            priority: None,
+        };
+        let result = update_activities(
+            1,
+            get_session(account_id),
+            store.clone(),
+            EventBus::default(),
+            for_update,
+        )
+        .await;
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+
+use crate::store::Store;
+use crate::types::account::Session;
+use crate::types::availability::{
+    Availability, AvailabilityRule, NewAvailabilityRule, NewTimeOff, TimeOff,
+};
+use tracing::{info, instrument};
+use warp::http::StatusCode;
+use warp::reply::json;
+
+#[instrument]
+#[utoipa::path(
+        get,
+        path = "account/availability",
+        responses(
+            (status = 200, description = "Weekly working hours, breaks and time off", body = Availability),
+            (status = 404, description = "Rout not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_availability(
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering availability");
+    let res: Availability = match store.get_availability(session.account_id).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+
+    Ok(warp::reply::json(&res))
+}
+
+#[utoipa::path(
+        post,
+        path = "account/availability/rules",
+        request_body = NewAvailabilityRule,
+        responses(
+            (status = 201, description = "rule added", body = AvailabilityRule),
+            (status = 422, description = "rule should end after start")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn add_rule(
+    session: Session,
+    store: Store,
+    rule: NewAvailabilityRule,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("add availability rule");
+    save_rule(None, session, store, rule).await
+}
+
+#[utoipa::path(
+        put,
+        path = "account/availability/rules/{id}",
+        request_body = NewAvailabilityRule,
+        params(
+            ("id" = i32, Path, description = "Rule unique id")
+        ),
+        responses(
+            (status = 201, description = "rule updated", body = AvailabilityRule),
+            (status = 404, description = "rule not found"),
+            (status = 422, description = "rule should end after start")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn update_rule(
+    id: i32,
+    session: Session,
+    store: Store,
+    rule: NewAvailabilityRule,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("update availability rule");
+    save_rule(Some(id), session, store, rule).await
+}
+
+async fn save_rule(
+    id: Option<i32>,
+    session: Session,
+    store: Store,
+    rule: NewAvailabilityRule,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    rule.validate()?;
+    match store
+        .save_availability_rule(rule, id, session.account_id)
+        .await
+    {
+        Ok(Some(res)) => Ok(warp::reply::with_status(json(&res), StatusCode::CREATED)),
+        Ok(None) => Ok(warp::reply::with_status(
+            json(&"Rule not found".to_string()),
+            StatusCode::NOT_FOUND,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[utoipa::path(
+        delete,
+        path = "account/availability/rules/{id}",
+        params(
+            ("id" = i32, Path, description = "Rule unique id")
+        ),
+        responses(
+            (status = 200, description = "rule deleted", body = i32),
+            (status = 404, description = "rule not found"),
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn delete_rule(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("delete availability rule");
+    match store.delete_availability_rule(id, session.account_id).await {
+        Ok(true) => {
+            let answer = HashMap::from([("Rule deleted with id", id)]);
+            Ok(warp::reply::with_status(json(&answer), StatusCode::OK))
+        }
+        Ok(false) => Ok(warp::reply::with_status(
+            json(&"Rule not found".to_string()),
+            StatusCode::NOT_FOUND,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[utoipa::path(
+        post,
+        path = "account/time_off",
+        request_body = NewTimeOff,
+        responses(
+            (status = 201, description = "time off added", body = TimeOff),
+            (status = 422, description = "time off should end after start")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn add_time_off(
+    session: Session,
+    store: Store,
+    time_off: NewTimeOff,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("add time off");
+    save_time_off(None, session, store, time_off).await
+}
+
+#[utoipa::path(
+        put,
+        path = "account/time_off/{id}",
+        request_body = NewTimeOff,
+        params(
+            ("id" = i32, Path, description = "Time off unique id")
+        ),
+        responses(
+            (status = 201, description = "time off updated", body = TimeOff),
+            (status = 404, description = "time off not found"),
+            (status = 422, description = "time off should end after start")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn update_time_off(
+    id: i32,
+    session: Session,
+    store: Store,
+    time_off: NewTimeOff,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("update time off");
+    save_time_off(Some(id), session, store, time_off).await
+}
+
+async fn save_time_off(
+    id: Option<i32>,
+    session: Session,
+    store: Store,
+    time_off: NewTimeOff,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    time_off.validate()?;
+    match store.save_time_off(time_off, id, session.account_id).await {
+        Ok(Some(res)) => Ok(warp::reply::with_status(json(&res), StatusCode::CREATED)),
+        Ok(None) => Ok(warp::reply::with_status(
+            json(&"Time off not found".to_string()),
+            StatusCode::NOT_FOUND,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[utoipa::path(
+        delete,
+        path = "account/time_off/{id}",
+        params(
+            ("id" = i32, Path, description = "Time off unique id")
+        ),
+        responses(
+            (status = 200, description = "time off deleted", body = i32),
+            (status = 404, description = "time off not found"),
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn delete_time_off(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("delete time off");
+    match store.delete_time_off(id, session.account_id).await {
+        Ok(true) => {
+            let answer = HashMap::from([("Time off deleted with id", id)]);
+            Ok(warp::reply::with_status(json(&answer), StatusCode::OK))
+        }
+        Ok(false) => Ok(warp::reply::with_status(
+            json(&"Time off not found".to_string()),
+            StatusCode::NOT_FOUND,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[cfg(test)]
+mod test_availability {
+    use crate::routes::availability::{add_rule, add_time_off, delete_rule, update_rule};
+    use crate::tests::helpers::{create_postgres, get_session, prepare_store};
+    use crate::types::account::AccountID;
+    use crate::types::availability::{NewAvailabilityRule, NewTimeOff, RuleKind};
+    use crate::types::recurrence::Day;
+    use testcontainers_modules::testcontainers::clients::Cli;
+    use warp::reply::Reply;
+
+    fn work_rule() -> NewAvailabilityRule {
+        NewAvailabilityRule {
+            weekday: Day::Mon,
+            kind: RuleKind::Work,
+            start_time: "09:00:00".parse().unwrap(),
+            end_time: "17:00:00".parse().unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_can_manage_availability() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        let result = add_rule(get_session(1), store.clone(), work_rule())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 201);
+        let time_off = NewTimeOff {
+            starts_at: "2026-12-24T00:00:00Z".parse().unwrap(),
+            ends_at: "2026-12-27T00:00:00Z".parse().unwrap(),
+            reason: Some("holidays".to_string()),
+        };
+        let result = add_time_off(get_session(1), store.clone(), time_off)
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 201);
+
+        let availability = store.clone().get_availability(AccountID(1)).await.unwrap();
+        assert_eq!(availability.rules.len(), 1);
+        assert_eq!(availability.time_off.len(), 1);
+
+        let result = update_rule(2, get_session(1), store.clone(), work_rule())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 404);
+        let result = delete_rule(1, get_session(2), store.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 404);
+        let result = delete_rule(1, get_session(1), store.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn medium_test_rule_should_end_after_start() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let mut rule = work_rule();
+        rule.end_time = rule.start_time;
+        let result = add_rule(get_session(1), store.clone(), rule).await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,506 @@
+use std::collections::HashMap;
+
+use crate::events::EventBus;
+use crate::store::Store;
+use crate::types::account::{AccountID, Session};
+use crate::types::activities::ActivityId;
+use crate::types::entries::{NewTimeEntry, TimeEntry, TimeEntryRange};
+use crate::types::events::Event;
+use crate::types::revisions::RevisionKind;
+use tracing::{error, info, instrument};
+use warp::http::StatusCode;
+use warp::reply::json;
+
+#[instrument]
+#[utoipa::path(
+        get,
+        path = "activity/{id}/entries",
+        responses(
+            (status = 200, description = "List time entries of activity", body = [TimeEntry]),
+            (status = 404, description = "activity not found")
+        ),
+        params(
+            ("id" = i32, Path, description = "Activity unique id")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_entries(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering time entries");
+    let account_id = session.account_id;
+
+    if !store.is_activity_owner(id, &account_id).await? {
+        return Ok(warp::reply::with_status(
+            json(&"Activity not found".to_string()),
+            StatusCode::NOT_FOUND,
+        ));
+    }
+
+    let res: Vec<TimeEntry> = match store.get_time_entries(account_id, id).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+
+    Ok(warp::reply::with_status(json(&res), StatusCode::OK))
+}
+
+#[utoipa::path(
+        post,
+        path = "activity/{id}/entries",
+        request_body = TimeEntryRange,
+        params(
+            ("id" = i32, Path, description = "Activity unique id")
+        ),
+        responses(
+            (status = 201, description = "time entry logged", body = TimeEntry),
+            (status = 404, description = "activity not found"),
+            (status = 409, description = "time entry overlaps another entry"),
+            (status = 422, description = "time range should end after start")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn add_entry(
+    id: i32,
+    session: Session,
+    store: Store,
+    events: EventBus,
+    range: TimeEntryRange,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("log time entry");
+    let account_id = session.account_id;
+
+    if !store.is_activity_owner(id, &account_id).await? {
+        return Ok(warp::reply::with_status(
+            json(&"Activity not found".to_string()),
+            StatusCode::NOT_FOUND,
+        ));
+    }
+    let entry = entry_from_range(id, range)?;
+
+    let res = match store
+        .clone()
+        .book_time_entry(entry, account_id.clone(), RevisionKind::EntryAdded)
+        .await
+    {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    publish_spent(id, &account_id, store, &events).await;
+    Ok(warp::reply::with_status(json(&res), StatusCode::CREATED))
+}
+
+#[utoipa::path(
+        put,
+        path = "activity/{id}/entries/{entry_id}",
+        request_body = TimeEntryRange,
+        params(
+            ("id" = i32, Path, description = "Activity unique id"),
+            ("entry_id" = i32, Path, description = "Time entry unique id")
+        ),
+        responses(
+            (status = 201, description = "time entry updated", body = TimeEntry),
+            (status = 404, description = "time entry not found"),
+            (status = 409, description = "time entry overlaps another entry"),
+            (status = 422, description = "time range should end after start")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn update_entry(
+    id: i32,
+    entry_id: i32,
+    session: Session,
+    store: Store,
+    events: EventBus,
+    range: TimeEntryRange,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("update time entry");
+    let account_id = session.account_id;
+    let entry = entry_from_range(id, range)?;
+
+    let res = match store
+        .clone()
+        .update_time_entry(entry_id, entry, account_id.clone())
+        .await
+    {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    publish_spent(id, &account_id, store, &events).await;
+    Ok(warp::reply::with_status(json(&res), StatusCode::CREATED))
+}
+
+#[utoipa::path(
+        delete,
+        path = "activity/{id}/entries/{entry_id}",
+        params(
+            ("id" = i32, Path, description = "Activity unique id"),
+            ("entry_id" = i32, Path, description = "Time entry unique id")
+        ),
+        responses(
+            (status = 200, description = "time entry deleted", body = i32),
+            (status = 404, description = "time entry not found"),
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn delete_entry(
+    id: i32,
+    entry_id: i32,
+    session: Session,
+    store: Store,
+    events: EventBus,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("delete time entry");
+    let account_id = session.account_id;
+    if let Err(e) = store
+        .clone()
+        .delete_time_entry(entry_id, id, account_id.clone())
+        .await
+    {
+        return Err(warp::reject::custom(e));
+    }
+    publish_spent(id, &account_id, store, &events).await;
+
+    let answer = HashMap::from([("Time entry deleted with id", entry_id)]);
+    Ok(warp::reply::with_status(json(&answer), StatusCode::OK))
+}
+
+/// Pushes the activity with its new spent time to the event streams
+async fn publish_spent(id: i32, account_id: &AccountID, store: Store, events: &EventBus) {
+    match store.get_activity_by_id(account_id.clone(), id).await {
+        Ok(activity) => events.publish(account_id, Event::ActivityUpdated { activity }),
+        Err(e) => error!("Can't get activity {} to publish with {:?}", id, e),
+    }
+}
+
+pub fn entry_from_range(
+    id: i32,
+    range: TimeEntryRange,
+) -> Result<NewTimeEntry, handle_errors::Error> {
+    if range.stopped_at <= range.started_at {
+        return Err(handle_errors::Error::InvalidTimeRange);
+    }
+    Ok(NewTimeEntry {
+        activity_id: ActivityId(id),
+        started_at: range.started_at,
+        stopped_at: range.stopped_at,
+        duration: (range.stopped_at - range.started_at).num_seconds() as i32,
+        needs_review: false,
+    })
+}
+
+#[cfg(test)]
+mod test_entries {
+    use crate::events::EventBus;
+    use crate::routes::entries::{
+        add_entry, delete_entry, entry_from_range, get_entries, update_entry,
+    };
+    use crate::routes::timer::{start, stop};
+    use crate::tests::helpers::{
+        convert_to_string, create_postgres, create_redis, get_session, prepare_cache, prepare_store,
+    };
+    use crate::types::account::AccountID;
+    use crate::types::entries::{TimeEntry, TimeEntryRange};
+    use crate::types::events::Event;
+    use crate::types::revisions::RevisionKind;
+    use crate::types::timer::TimerOptions;
+    use chrono::{TimeDelta, Utc};
+    use testcontainers::clients::Cli;
+    use tokio_stream::StreamExt;
+    use warp::reply::Reply;
+
+    #[tokio::test]
+    async fn medium_test_stop_timer_books_time_entry() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+        stop(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+        )
+        .await
+        .unwrap();
+
+        let result = get_entries(1, get_session(1), store.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 200);
+        let body = warp::hyper::body::to_bytes(result.into_body())
+            .await
+            .unwrap();
+        let entries: Vec<TimeEntry> =
+            serde_json::from_str(&convert_to_string(&body).await.unwrap()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].activity_id.0, 1);
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_should_not_get_entries_of_not_owned_activity() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let result = get_entries(1, get_session(2), store.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 404);
+    }
+
+    #[test]
+    fn small_test_entry_duration_is_the_whole_range() {
+        let started_at = Utc::now();
+        let range = TimeEntryRange {
+            started_at,
+            stopped_at: started_at + TimeDelta::minutes(45),
+        };
+        let entry = entry_from_range(1, range).unwrap();
+        assert_eq!(entry.duration, 45 * 60);
+        assert!(!entry.needs_review);
+    }
+
+    #[test]
+    fn small_test_entry_should_end_after_start() {
+        let started_at = Utc::now();
+        let range = TimeEntryRange {
+            started_at,
+            stopped_at: started_at,
+        };
+        assert!(entry_from_range(1, range).is_err());
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_can_log_update_and_delete_time() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let started_at = Utc::now() - TimeDelta::days(1);
+        let range = TimeEntryRange {
+            started_at,
+            stopped_at: started_at + TimeDelta::minutes(45),
+        };
+        let result = add_entry(1, get_session(1), store.clone(), EventBus::default(), range)
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 201);
+        let activity = store
+            .clone()
+            .get_activity_by_id(AccountID(1), 1)
+            .await
+            .unwrap();
+        assert_eq!(activity.spent, 45 * 60);
+
+        let range = TimeEntryRange {
+            started_at,
+            stopped_at: started_at + TimeDelta::minutes(30),
+        };
+        let result = update_entry(
+            1,
+            1,
+            get_session(1),
+            store.clone(),
+            EventBus::default(),
+            range,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(result.status(), 201);
+        let activity = store
+            .clone()
+            .get_activity_by_id(AccountID(1), 1)
+            .await
+            .unwrap();
+        assert_eq!(activity.spent, 30 * 60);
+
+        let result = delete_entry(1, 1, get_session(1), store.clone(), EventBus::default())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 200);
+        let activity = store
+            .clone()
+            .get_activity_by_id(AccountID(1), 1)
+            .await
+            .unwrap();
+        assert_eq!(activity.spent, 0);
+    }
+
+    #[tokio::test]
+    async fn medium_test_entries_publish_activity_with_spent_time() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let events = EventBus::default();
+        let mut stream = Box::pin(events.subscribe(AccountID(1)));
+        let started_at = Utc::now() - TimeDelta::days(1);
+        let range = TimeEntryRange {
+            started_at,
+            stopped_at: started_at + TimeDelta::minutes(45),
+        };
+        add_entry(1, get_session(1), store.clone(), events.clone(), range)
+            .await
+            .unwrap();
+        let range = TimeEntryRange {
+            started_at,
+            stopped_at: started_at + TimeDelta::minutes(30),
+        };
+        update_entry(1, 1, get_session(1), store.clone(), events.clone(), range)
+            .await
+            .unwrap();
+        delete_entry(1, 1, get_session(1), store.clone(), events)
+            .await
+            .unwrap();
+
+        for spent in [45 * 60, 30 * 60, 0] {
+            match stream.next().await {
+                Some(Event::ActivityUpdated { activity }) => assert_eq!(activity.spent, spent),
+                other => panic!("unexpected event {:?}", other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn medium_test_entry_changes_keep_spent_history() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let started_at = Utc::now() - TimeDelta::days(1);
+        let range = TimeEntryRange {
+            started_at,
+            stopped_at: started_at + TimeDelta::minutes(45),
+        };
+        add_entry(1, get_session(1), store.clone(), EventBus::default(), range)
+            .await
+            .unwrap();
+        let range = TimeEntryRange {
+            started_at,
+            stopped_at: started_at + TimeDelta::minutes(30),
+        };
+        update_entry(
+            1,
+            1,
+            get_session(1),
+            store.clone(),
+            EventBus::default(),
+            range,
+        )
+        .await
+        .unwrap();
+        delete_entry(1, 1, get_session(1), store.clone(), EventBus::default())
+            .await
+            .unwrap();
+
+        let revisions = store.get_revisions(1, AccountID(1)).await.unwrap();
+        let kinds: Vec<RevisionKind> = revisions.iter().map(|revision| revision.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                RevisionKind::EntryAdded,
+                RevisionKind::EntryUpdated,
+                RevisionKind::EntryDeleted
+            ]
+        );
+        let spent: Vec<(i32, i32)> = revisions
+            .iter()
+            .map(|revision| (revision.before.spent, revision.after.spent))
+            .collect();
+        assert_eq!(spent, vec![(0, 45 * 60), (45 * 60, 30 * 60), (30 * 60, 0)]);
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_cant_log_overlapping_time() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let started_at = Utc::now() - TimeDelta::days(1);
+        let range = TimeEntryRange {
+            started_at,
+            stopped_at: started_at + TimeDelta::minutes(45),
+        };
+        add_entry(1, get_session(1), store.clone(), EventBus::default(), range)
+            .await
+            .unwrap();
+        let range = TimeEntryRange {
+            started_at: started_at + TimeDelta::minutes(30),
+            stopped_at: started_at + TimeDelta::minutes(60),
+        };
+        let result = add_entry(1, get_session(1), store.clone(), EventBus::default(), range).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn medium_test_concurrent_overlapping_entries_book_once() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let started_at = Utc::now() - TimeDelta::days(1);
+        let range = TimeEntryRange {
+            started_at,
+            stopped_at: started_at + TimeDelta::minutes(45),
+        };
+        let (first, second) = tokio::join!(
+            add_entry(
+                1,
+                get_session(1),
+                store.clone(),
+                EventBus::default(),
+                range.clone()
+            ),
+            add_entry(1, get_session(1), store.clone(), EventBus::default(), range),
+        );
+        assert!(first.is_ok() != second.is_ok());
+        let entries = store
+            .clone()
+            .get_time_entries(AccountID(1), 1)
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        let activity = store
+            .clone()
+            .get_activity_by_id(AccountID(1), 1)
+            .await
+            .unwrap();
+        assert_eq!(activity.spent, 45 * 60);
+    }
+}
@@ -0,0 +1,36 @@
+use std::convert::Infallible;
+
+use crate::events::EventBus;
+use crate::types::account::Session;
+use crate::types::events::Event;
+use tokio_stream::StreamExt;
+use tracing::info;
+use warp::sse;
+
+#[utoipa::path(
+        get,
+        path = "events",
+        responses(
+            (status = 200, description = "Server-Sent Events stream of timer and activity changes", body = Event, content_type = "text/event-stream"),
+            (status = 404, description = "Not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn events(
+    session: Session,
+    events: EventBus,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("open event stream for: {:?}", session.account_id);
+    let stream =
+        events
+            .subscribe(session.account_id)
+            .map(|event| -> Result<sse::Event, Infallible> {
+                Ok(sse::Event::default()
+                    .event(event.name())
+                    .json_data(&event)
+                    .unwrap_or_default())
+            });
+    Ok(sse::reply(sse::keep_alive().stream(stream)))
+}
@@ -0,0 +1,174 @@
+use chrono::TimeDelta;
+
+use crate::schedule::{busy_intervals, free_slots, merge, working_intervals, Interval};
+use crate::store::Store;
+use crate::types::account::Session;
+use crate::types::freebusy::FreeBusy;
+use crate::types::recurrence::OccurrenceRange;
+use tracing::info;
+use warp::http::StatusCode;
+use warp::reply::json;
+
+#[utoipa::path(
+        get,
+        path = "freebusy",
+        params(OccurrenceRange),
+        responses(
+            (status = 200, description = "Busy and free intervals of the account", body = FreeBusy),
+            (status = 422, description = "range should end after start and be at most a year")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_freebusy(
+    session: Session,
+    range: OccurrenceRange,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering free/busy");
+    range.validate()?;
+    let account_id = session.account_id;
+    let whole = Interval::new(range.from, range.to);
+    let availability = store.clone().get_availability(account_id.clone()).await?;
+    let preferences = store.clone().get_preferences(account_id.clone()).await?;
+    let tz = preferences.tz();
+
+    let mut busy = busy_intervals(&store, &account_id, &preferences, range.from, range.to).await?;
+    busy.extend(
+        availability
+            .time_off
+            .iter()
+            .map(|time_off| Interval::new(time_off.starts_at, time_off.ends_at)),
+    );
+    let busy: Vec<Interval> = merge(busy)
+        .into_iter()
+        .map(|interval| Interval::new(interval.start.max(range.from), interval.end.min(range.to)))
+        .filter(|interval| !interval.is_empty())
+        .collect();
+
+    let windows = if availability.rules.is_empty() {
+        vec![whole]
+    } else {
+        let mut windows = vec![];
+        let mut date = range.from.with_timezone(&tz).date_naive();
+        while date <= range.to.with_timezone(&tz).date_naive() {
+            windows.extend(working_intervals(&availability, date, &tz));
+            date += TimeDelta::days(1);
+        }
+        windows
+    };
+    let free = windows
+        .into_iter()
+        .map(|window| Interval::new(window.start.max(range.from), window.end.min(range.to)))
+        .filter(|window| !window.is_empty())
+        .flat_map(|window| free_slots(window, busy.clone()))
+        .collect();
+
+    let res = FreeBusy {
+        from: range.from,
+        to: range.to,
+        busy,
+        free,
+    };
+    Ok(warp::reply::with_status(json(&res), StatusCode::OK))
+}
+
+#[cfg(test)]
+mod test_freebusy {
+    use crate::routes::freebusy::get_freebusy;
+    use crate::tests::helpers::{convert_to_string, create_postgres, get_session, prepare_store};
+    use crate::types::account::AccountID;
+    use crate::types::activities::{ActivityStatus, NewActivity};
+    use crate::types::freebusy::FreeBusy;
+    use crate::types::recurrence::{Frequency, NewRecurrence, OccurrenceRange};
+    use testcontainers_modules::testcontainers::clients::Cli;
+    use warp::reply::Reply;
+
+    #[tokio::test]
+    async fn medium_test_freebusy_splits_range_around_scheduled_activity() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        let activity = NewActivity {
+            title: "meeting".to_string(),
+            content: "test".to_string(),
+            time: Some(3600),
+            scheduled_start: Some("2026-10-19T10:00:00Z".parse().unwrap()),
+            scheduled_end: Some("2026-10-19T11:00:00Z".parse().unwrap()),
+            due_at: None,
+            project_id: None,
+            parent_id: None,
+            priority: None,
+        };
+        store
+            .clone()
+            .add_activity(activity, AccountID(1))
+            .await
+            .unwrap();
+        let range = OccurrenceRange {
+            from: "2026-10-19T09:00:00Z".parse().unwrap(),
+            to: "2026-10-19T12:00:00Z".parse().unwrap(),
+        };
+        let result = get_freebusy(get_session(1), range, store.clone())
+            .await
+            .unwrap()
+            .into_response();
+        let body = warp::hyper::body::to_bytes(result.into_body())
+            .await
+            .unwrap();
+        let freebusy: FreeBusy =
+            serde_json::from_str(&convert_to_string(&body).await.unwrap()).unwrap();
+        assert_eq!(freebusy.busy.len(), 1);
+        assert_eq!(freebusy.free.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn medium_test_cancelled_recurring_activity_is_not_busy() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let recurrence = NewRecurrence {
+            frequency: Frequency::Daily,
+            interval: None,
+            by_weekday: None,
+            starts_at: "2026-10-19T10:00:00Z".parse().unwrap(),
+            until: None,
+            count: None,
+            exceptions: None,
+        };
+        store
+            .clone()
+            .set_recurrence(recurrence.into_recurrence(1).unwrap(), AccountID(1))
+            .await
+            .unwrap();
+        store
+            .clone()
+            .set_status(
+                1,
+                ActivityStatus::Todo,
+                ActivityStatus::Cancelled,
+                AccountID(1),
+            )
+            .await
+            .unwrap();
+        let range = OccurrenceRange {
+            from: "2026-10-19T09:00:00Z".parse().unwrap(),
+            to: "2026-10-19T12:00:00Z".parse().unwrap(),
+        };
+        let result = get_freebusy(get_session(1), range, store.clone())
+            .await
+            .unwrap()
+            .into_response();
+        let body = warp::hyper::body::to_bytes(result.into_body())
+            .await
+            .unwrap();
+        let freebusy: FreeBusy =
+            serde_json::from_str(&convert_to_string(&body).await.unwrap()).unwrap();
+        assert!(freebusy.busy.is_empty());
+        assert_eq!(freebusy.free.len(), 1);
+    }
+}
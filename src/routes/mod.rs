@@ -1,4 +1,14 @@
 pub mod activities;
 pub mod authentication;
+pub mod availability;
+pub mod entries;
+pub mod events;
+pub mod freebusy;
 pub mod health;
+pub mod plan;
+pub mod preferences;
+pub mod projects;
+pub mod recurrence;
+pub mod tags;
 pub mod timer;
+pub mod trash;
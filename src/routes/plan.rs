@@ -0,0 +1,168 @@
+use chrono::prelude::*;
+
+use crate::events::EventBus;
+use crate::schedule::{busy_intervals, free_slots, plan, working_intervals, Interval};
+use crate::store::Store;
+use crate::types::account::Session;
+use crate::types::activities::{check_schedule, Activity};
+use crate::types::availability::{AvailabilityRule, RuleKind};
+use crate::types::events::Event;
+use crate::types::plan::{Plan, PlanQuery, PlannedBlock};
+use crate::types::preferences::day_bounds;
+use tracing::info;
+use warp::http::StatusCode;
+use warp::reply::json;
+
+#[utoipa::path(
+        post,
+        path = "plan",
+        params(PlanQuery),
+        responses(
+            (status = 200, description = "Proposed schedule of the day, nothing is saved", body = Plan),
+            (status = 422, description = "working hours should end after start")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn plan_day(
+    session: Session,
+    query: PlanQuery,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let account_id = session.account_id;
+    let preferences = store.clone().get_preferences(account_id.clone()).await?;
+    let tz = preferences.tz();
+    let now = Utc::now();
+    let date = query
+        .date
+        .unwrap_or_else(|| now.with_timezone(&tz).date_naive());
+    info!("plan day {}", date);
+    let hours = query.working_hours()?;
+    let mut availability = store.clone().get_availability(account_id.clone()).await?;
+    if hours.is_some() || availability.rules.is_empty() {
+        let (start_time, end_time) = hours.unwrap_or(PlanQuery::default_hours());
+        availability.rules = vec![AvailabilityRule {
+            id: 0,
+            weekday: date.weekday().into(),
+            kind: RuleKind::Work,
+            start_time,
+            end_time,
+        }];
+    }
+    let (start, end) = day_bounds(&tz, date);
+
+    let busy = busy_intervals(&store, &account_id, &preferences, start, end).await?;
+
+    let activities: Vec<Activity> = store.get_unscheduled(account_id).await?;
+    let free: Vec<Interval> = working_intervals(&availability, date, &tz)
+        .into_iter()
+        .map(|window| Interval::new(window.start.max(now), window.end))
+        .filter(|window| !window.is_empty())
+        .flat_map(|window| free_slots(window, busy.clone()))
+        .collect();
+    let (blocks, unplaced) = plan(free, &activities);
+    let res = Plan {
+        date,
+        blocks,
+        unplaced,
+    };
+    Ok(warp::reply::with_status(json(&res), StatusCode::OK))
+}
+
+#[utoipa::path(
+        post,
+        path = "plan/accept",
+        request_body = [PlannedBlock],
+        responses(
+            (status = 201, description = "Schedule saved to the activities", body = [Activity]),
+            (status = 422, description = "block should end after start or activity not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn accept_plan(
+    session: Session,
+    store: Store,
+    events: EventBus,
+    blocks: Vec<PlannedBlock>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("accept plan with {} blocks", blocks.len());
+    let account_id = session.account_id;
+    for block in &blocks {
+        check_schedule(Some(block.scheduled_start), Some(block.scheduled_end))?;
+    }
+
+    let res = match store.set_schedules(blocks, account_id.clone()).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    for activity in &res {
+        events.publish(
+            &account_id,
+            Event::ActivityUpdated {
+                activity: activity.clone(),
+            },
+        );
+    }
+    Ok(warp::reply::with_status(json(&res), StatusCode::CREATED))
+}
+
+#[cfg(test)]
+mod test_plan {
+    use crate::events::EventBus;
+    use crate::routes::plan::{accept_plan, plan_day};
+    use crate::tests::helpers::{convert_to_string, create_postgres, get_session, prepare_store};
+    use crate::types::account::AccountID;
+    use crate::types::plan::{Plan, PlanQuery};
+    use chrono::{TimeDelta, Utc};
+    use testcontainers_modules::testcontainers::clients::Cli;
+    use warp::reply::Reply;
+
+    #[tokio::test]
+    async fn medium_test_user_can_plan_and_accept_day() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        store.clone().add_test_acctivities().await;
+        let query = PlanQuery {
+            date: Some((Utc::now() + TimeDelta::days(1)).date_naive()),
+            work_start: None,
+            work_end: None,
+        };
+        let result = plan_day(get_session(1), query, store.clone())
+            .await
+            .unwrap()
+            .into_response();
+        let body = warp::hyper::body::to_bytes(result.into_body())
+            .await
+            .unwrap();
+        let plan: Plan = serde_json::from_str(&convert_to_string(&body).await.unwrap()).unwrap();
+        assert_eq!(plan.blocks.len(), 2);
+        assert!(plan.unplaced.is_empty());
+        assert_eq!(plan.blocks[0].scheduled_end, plan.blocks[1].scheduled_start);
+
+        let result = accept_plan(
+            get_session(1),
+            store.clone(),
+            EventBus::default(),
+            plan.blocks.clone(),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(result.status(), 201);
+        let activity = store
+            .clone()
+            .get_activity_by_id(AccountID(1), 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            activity.scheduled_start,
+            Some(plan.blocks[0].scheduled_start)
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::store::Store;
+use crate::types::account::Session;
+use crate::types::preferences::{PartialPreferences, Preferences};
+use tracing::{info, instrument};
+use warp::http::StatusCode;
+use warp::reply::json;
+
+#[instrument]
+#[utoipa::path(
+        get,
+        path = "account/preferences",
+        responses(
+            (status = 200, description = "Account preferences", body = Preferences),
+            (status = 404, description = "Rout not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_preferences(
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering preferences");
+    let res: Preferences = match store.get_preferences(session.account_id).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+
+    Ok(warp::reply::json(&res))
+}
+
+#[utoipa::path(
+        put,
+        path = "account/preferences",
+        request_body = PartialPreferences,
+        responses(
+            (status = 201, description = "preferences updated", body = Preferences),
+            (status = 422, description = "can't update preferences or unknown time zone")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn update_preferences(
+    session: Session,
+    store: Store,
+    new_preferences: PartialPreferences,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("update preferences");
+    let account_id = session.account_id;
+    let old_preferences = store.clone().get_preferences(account_id.clone()).await?;
+
+    let preferences = Preferences {
+        single_active_timer: new_preferences
+            .single_active_timer
+            .unwrap_or(old_preferences.single_active_timer),
+        timezone: new_preferences.timezone.unwrap_or(old_preferences.timezone),
+        week_start: new_preferences
+            .week_start
+            .unwrap_or(old_preferences.week_start),
+        default_activity_duration: new_preferences
+            .default_activity_duration
+            .unwrap_or(old_preferences.default_activity_duration),
+        time_format: new_preferences
+            .time_format
+            .unwrap_or(old_preferences.time_format),
+    };
+    preferences.validate()?;
+
+    let res = match store.update_preferences(preferences, account_id).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::with_status(json(&res), StatusCode::CREATED))
+}
+
+#[cfg(test)]
+mod test_preferences {
+    use crate::routes::preferences::update_preferences;
+    use crate::tests::helpers::{create_postgres, get_session, prepare_store};
+    use crate::types::account::AccountID;
+    use crate::types::preferences::PartialPreferences;
+    use testcontainers_modules::testcontainers::clients::Cli;
+    use warp::reply::Reply;
+
+    #[tokio::test]
+    async fn medium_test_preferences_default_to_disabled_options() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        let result = store.get_preferences(AccountID(1)).await.unwrap();
+        assert!(!result.single_active_timer);
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_can_update_preferences() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        let for_update = PartialPreferences {
+            single_active_timer: Some(true),
+            timezone: Some("America/New_York".to_string()),
+            week_start: None,
+            default_activity_duration: None,
+            time_format: None,
+        };
+        let result = update_preferences(get_session(1), store.clone(), for_update)
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 201);
+        let result = store.get_preferences(AccountID(1)).await.unwrap();
+        assert!(result.single_active_timer);
+        assert_eq!(result.timezone, "America/New_York");
+        assert_eq!(result.default_activity_duration, 30);
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_cant_set_unknown_time_zone() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        let for_update = PartialPreferences {
+            single_active_timer: None,
+            timezone: Some("Mars/Olympus".to_string()),
+            week_start: None,
+            default_activity_duration: None,
+            time_format: None,
+        };
+        let result = update_preferences(get_session(1), store.clone(), for_update).await;
+        assert!(result.is_err());
+    }
+}
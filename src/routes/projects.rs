@@ -0,0 +1,368 @@
+use std::collections::HashMap;
+
+use crate::store::Store;
+use crate::types::account::Session;
+use crate::types::activities::Activity;
+use crate::types::projects::{ActivityTree, NewProject, Project};
+use tracing::{info, instrument};
+use warp::http::StatusCode;
+use warp::reply::json;
+
+#[instrument]
+#[utoipa::path(
+        get,
+        path = "project",
+        responses(
+            (status = 200, description = "Projects of the account with their time rolled up", body = [Project]),
+            (status = 404, description = "Rout not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_projects(
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering projects");
+    let res: Vec<Project> = match store.get_projects(session.account_id).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+
+    Ok(warp::reply::json(&res))
+}
+
+#[instrument]
+#[utoipa::path(
+        get,
+        path = "project/{id}",
+        params(
+            ("id" = i32, Path, description = "Project unique id")
+        ),
+        responses(
+            (status = 200, description = "Project with its time rolled up", body = Project),
+            (status = 404, description = "project not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_project(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering project");
+    match store.get_project(id, session.account_id).await {
+        Ok(Some(res)) => Ok(warp::reply::with_status(json(&res), StatusCode::OK)),
+        Ok(None) => Ok(warp::reply::with_status(
+            json(&"Project not found".to_string()),
+            StatusCode::NOT_FOUND,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[utoipa::path(
+        post,
+        path = "project",
+        request_body = NewProject,
+        responses(
+            (status = 201, description = "project added", body = Project),
+            (status = 422, description = "project title is empty")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn add_project(
+    session: Session,
+    store: Store,
+    project: NewProject,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("add project");
+    save_project(None, session, store, project).await
+}
+
+#[utoipa::path(
+        put,
+        path = "project/{id}",
+        request_body = NewProject,
+        params(
+            ("id" = i32, Path, description = "Project unique id")
+        ),
+        responses(
+            (status = 201, description = "project updated", body = Project),
+            (status = 404, description = "project not found"),
+            (status = 422, description = "project title is empty")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn update_project(
+    id: i32,
+    session: Session,
+    store: Store,
+    project: NewProject,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("update project");
+    save_project(Some(id), session, store, project).await
+}
+
+async fn save_project(
+    id: Option<i32>,
+    session: Session,
+    store: Store,
+    project: NewProject,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    project.validate()?;
+    match store.save_project(project, id, session.account_id).await {
+        Ok(Some(res)) => Ok(warp::reply::with_status(json(&res), StatusCode::CREATED)),
+        Ok(None) => Ok(warp::reply::with_status(
+            json(&"Project not found".to_string()),
+            StatusCode::NOT_FOUND,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[utoipa::path(
+        delete,
+        path = "project/{id}",
+        params(
+            ("id" = i32, Path, description = "Project unique id")
+        ),
+        responses(
+            (status = 200, description = "project deleted, its activities are kept", body = i32),
+            (status = 404, description = "project not found"),
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn delete_project(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("delete project");
+    match store.delete_project(id, session.account_id).await {
+        Ok(true) => {
+            let answer = HashMap::from([("Project deleted with id", id)]);
+            Ok(warp::reply::with_status(json(&answer), StatusCode::OK))
+        }
+        Ok(false) => Ok(warp::reply::with_status(
+            json(&"Project not found".to_string()),
+            StatusCode::NOT_FOUND,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[utoipa::path(
+        get,
+        path = "project/{id}/activities",
+        params(
+            ("id" = i32, Path, description = "Project unique id")
+        ),
+        responses(
+            (status = 200, description = "Activities of the project", body = [Activity]),
+            (status = 404, description = "project not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_project_activities(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering project activities");
+    let account_id = session.account_id;
+
+    if store
+        .clone()
+        .get_project(id, account_id.clone())
+        .await?
+        .is_none()
+    {
+        return Ok(warp::reply::with_status(
+            json(&"Project not found".to_string()),
+            StatusCode::NOT_FOUND,
+        ));
+    }
+    let res: Vec<Activity> = match store.get_project_activities(id, account_id).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::with_status(json(&res), StatusCode::OK))
+}
+
+#[utoipa::path(
+        get,
+        path = "activity/{id}/tree",
+        params(
+            ("id" = i32, Path, description = "Activity unique id")
+        ),
+        responses(
+            (status = 200, description = "Activity with its sub-activities and time rolled up", body = ActivityTree),
+            (status = 404, description = "activity not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_activity_tree(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering activity tree");
+    let account_id = session.account_id;
+
+    if !store.is_activity_owner(id, &account_id).await? {
+        return Ok(warp::reply::with_status(
+            json(&"Activity not found".to_string()),
+            StatusCode::NOT_FOUND,
+        ));
+    }
+    let root = store
+        .clone()
+        .get_activity_by_id(account_id.clone(), id)
+        .await?;
+    let descendants = store.get_descendants(id, account_id).await?;
+    let res = ActivityTree::build(root, &descendants);
+    Ok(warp::reply::with_status(json(&res), StatusCode::OK))
+}
+
+#[cfg(test)]
+mod test_projects {
+    use crate::events::EventBus;
+    use crate::routes::activities::{add_activity, update_activities};
+    use crate::routes::projects::{add_project, delete_project, get_activity_tree};
+    use crate::tests::helpers::{convert_to_string, create_postgres, get_session, prepare_store};
+    use crate::types::account::AccountID;
+    use crate::types::activities::{ActivityId, NewActivity, PartiaActivity};
+    use crate::types::projects::{ActivityTree, NewProject, Project, ProjectId};
+    use testcontainers_modules::testcontainers::clients::Cli;
+    use warp::reply::Reply;
+
+    fn child(parent_id: Option<ActivityId>, time: i32) -> NewActivity {
+        NewActivity {
+            title: "test".to_string(),
+            content: "test".to_string(),
+            time: Some(time),
+            scheduled_start: None,
+            scheduled_end: None,
+            due_at: None,
+            project_id: None,
+            parent_id,
+            priority: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn medium_test_children_roll_up_to_parent_and_project() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        let project = NewProject {
+            title: "client".to_string(),
+            content: "".to_string(),
+        };
+        let result = add_project(get_session(1), store.clone(), project)
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 201);
+        let body = warp::hyper::body::to_bytes(result.into_body())
+            .await
+            .unwrap();
+        let project: Project =
+            serde_json::from_str(&convert_to_string(&body).await.unwrap()).unwrap();
+
+        let mut root = child(None, 10);
+        root.project_id = Some(project.id);
+        for new in [
+            root,
+            child(Some(ActivityId(1)), 20),
+            child(Some(ActivityId(2)), 30),
+        ] {
+            add_activity(get_session(1), store.clone(), EventBus::default(), new)
+                .await
+                .unwrap();
+        }
+
+        let result = get_activity_tree(1, get_session(1), store.clone())
+            .await
+            .unwrap()
+            .into_response();
+        let body = warp::hyper::body::to_bytes(result.into_body())
+            .await
+            .unwrap();
+        let tree: ActivityTree =
+            serde_json::from_str(&convert_to_string(&body).await.unwrap()).unwrap();
+        assert_eq!(tree.rollup.time, 60 * 60);
+        assert_eq!(tree.children[0].rollup.time, 50 * 60);
+        assert_eq!(
+            tree.children[0].children[0].activity.project_id,
+            Some(project.id)
+        );
+
+        let project = store
+            .clone()
+            .get_project(project.id.0, AccountID(1))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(project.rollup.remaining, 60 * 60);
+
+        delete_project(project.id.0, get_session(1), store.clone())
+            .await
+            .unwrap();
+        let activity = store.get_activity_by_id(AccountID(1), 3).await.unwrap();
+        assert_eq!(activity.project_id, None);
+    }
+
+    #[tokio::test]
+    async fn medium_test_activity_cant_be_its_own_ancestor() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        for new in [child(None, 10), child(Some(ActivityId(1)), 20)] {
+            add_activity(get_session(1), store.clone(), EventBus::default(), new)
+                .await
+                .unwrap();
+        }
+        let partial = PartiaActivity {
+            title: None,
+            content: None,
+            time: None,
+            scheduled_start: None,
+            scheduled_end: None,
+            due_at: None,
+            project_id: None,
+            parent_id: Some(Some(ActivityId(2))),
+            priority: None,
+        };
+        let result = update_activities(
+            1,
+            get_session(1),
+            store.clone(),
+            EventBus::default(),
+            partial,
+        )
+        .await;
+        assert!(result.is_err());
+
+        let mut other = child(None, 10);
+        other.project_id = Some(ProjectId(42));
+        let result = add_activity(get_session(1), store.clone(), EventBus::default(), other).await;
+        assert!(result.is_err());
+    }
+}
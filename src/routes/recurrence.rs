@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use crate::store::Store;
+use crate::types::account::Session;
+use crate::types::recurrence::{NewRecurrence, Occurrence, OccurrenceRange, Recurrence};
+use chrono::TimeDelta;
+use tracing::info;
+use warp::http::StatusCode;
+use warp::reply::json;
+
+#[utoipa::path(
+        get,
+        path = "activity/{id}/recurrence",
+        params(
+            ("id" = i32, Path, description = "Activity unique id")
+        ),
+        responses(
+            (status = 200, description = "Recurrence rule of activity", body = Recurrence),
+            (status = 404, description = "activity is not recurring")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_recurrence(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering recurrence");
+    match store.get_recurrence(id, session.account_id).await {
+        Ok(Some(res)) => Ok(warp::reply::with_status(json(&res), StatusCode::OK)),
+        Ok(None) => Ok(warp::reply::with_status(
+            json(&"Recurrence not found".to_string()),
+            StatusCode::NOT_FOUND,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[utoipa::path(
+        put,
+        path = "activity/{id}/recurrence",
+        request_body = NewRecurrence,
+        params(
+            ("id" = i32, Path, description = "Activity unique id")
+        ),
+        responses(
+            (status = 201, description = "recurrence set", body = Recurrence),
+            (status = 404, description = "activity not found"),
+            (status = 422, description = "recurrence rule is invalid")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn set_recurrence(
+    id: i32,
+    session: Session,
+    store: Store,
+    new_recurrence: NewRecurrence,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("set recurrence");
+    let account_id = session.account_id;
+
+    if !store.is_activity_owner(id, &account_id).await? {
+        return Ok(warp::reply::with_status(
+            json(&"Activity not found".to_string()),
+            StatusCode::NOT_FOUND,
+        ));
+    }
+    let recurrence = new_recurrence.into_recurrence(id)?;
+
+    let res = match store.set_recurrence(recurrence, account_id).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+    Ok(warp::reply::with_status(json(&res), StatusCode::CREATED))
+}
+
+#[utoipa::path(
+        delete,
+        path = "activity/{id}/recurrence",
+        params(
+            ("id" = i32, Path, description = "Activity unique id")
+        ),
+        responses(
+            (status = 200, description = "recurrence deleted", body = i32),
+            (status = 404, description = "activity is not recurring"),
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn delete_recurrence(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("delete recurrence");
+    match store.delete_recurrence(id, session.account_id).await {
+        Ok(true) => {
+            let answer = HashMap::from([("Recurrence deleted for activity", id)]);
+            Ok(warp::reply::with_status(json(&answer), StatusCode::OK))
+        }
+        Ok(false) => Ok(warp::reply::with_status(
+            json(&"Recurrence not found".to_string()),
+            StatusCode::NOT_FOUND,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[utoipa::path(
+        get,
+        path = "occurrences",
+        params(OccurrenceRange),
+        responses(
+            (status = 200, description = "Occurrences of recurring activities in the range", body = [Occurrence]),
+            (status = 422, description = "range should end after start and be at most a year")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_occurrences(
+    session: Session,
+    range: OccurrenceRange,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering occurrences");
+    range.validate()?;
+    let preferences = store
+        .clone()
+        .get_preferences(session.account_id.clone())
+        .await?;
+    let tz = preferences.tz();
+    let activities = match store.get_recurring_activities(session.account_id).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+
+    let mut res: Vec<Occurrence> = activities
+        .iter()
+        .flat_map(|(activity, recurrence)| {
+            recurrence
+                .occurrences(range.from, range.to, &tz, preferences.week_start())
+                .into_iter()
+                .map(|starts_at| Occurrence {
+                    activity_id: activity.id.clone(),
+                    title: activity.title.clone(),
+                    starts_at,
+                    ends_at: starts_at + TimeDelta::seconds(activity.time as i64),
+                })
+        })
+        .collect();
+    res.sort_by_key(|occurrence| occurrence.starts_at);
+    Ok(warp::reply::json(&res))
+}
+
+#[cfg(test)]
+mod test_recurrence {
+    use crate::routes::recurrence::{delete_recurrence, get_occurrences, set_recurrence};
+    use crate::tests::helpers::{convert_to_string, create_postgres, get_session, prepare_store};
+    use crate::types::recurrence::{Day, Frequency, NewRecurrence, Occurrence, OccurrenceRange};
+    use testcontainers_modules::testcontainers::clients::Cli;
+    use warp::reply::Reply;
+
+    fn every_weekday() -> NewRecurrence {
+        NewRecurrence {
+            frequency: Frequency::Daily,
+            interval: None,
+            by_weekday: Some(vec![Day::Mon, Day::Tue, Day::Wed, Day::Thu, Day::Fri]),
+            starts_at: "2026-10-12T09:00:00Z".parse().unwrap(),
+            until: None,
+            count: None,
+            exceptions: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_can_expand_recurring_activity() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let result = set_recurrence(1, get_session(1), store.clone(), every_weekday())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 201);
+
+        let range = OccurrenceRange {
+            from: "2026-10-12T00:00:00Z".parse().unwrap(),
+            to: "2026-10-19T00:00:00Z".parse().unwrap(),
+        };
+        let result = get_occurrences(get_session(1), range, store.clone())
+            .await
+            .unwrap()
+            .into_response();
+        let body = warp::hyper::body::to_bytes(result.into_body())
+            .await
+            .unwrap();
+        let occurrences: Vec<Occurrence> =
+            serde_json::from_str(&convert_to_string(&body).await.unwrap()).unwrap();
+        assert_eq!(occurrences.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_cant_set_recurrence_of_not_owned_activity() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let result = set_recurrence(1, get_session(2), store.clone(), every_weekday())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 404);
+        let result = delete_recurrence(1, get_session(1), store.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 404);
+    }
+}
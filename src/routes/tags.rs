@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use crate::store::Store;
+use crate::types::account::Session;
+use crate::types::tags::{NewTag, Tag};
+use tracing::{info, instrument};
+use warp::http::StatusCode;
+use warp::reply::json;
+
+#[instrument]
+#[utoipa::path(
+        get,
+        path = "tags",
+        responses(
+            (status = 200, description = "Tags of the account", body = [Tag]),
+            (status = 404, description = "Rout not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_tags(session: Session, store: Store) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering tags");
+    let res: Vec<Tag> = match store.get_tags(session.account_id).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+
+    Ok(warp::reply::json(&res))
+}
+
+#[utoipa::path(
+        post,
+        path = "tags",
+        request_body = NewTag,
+        responses(
+            (status = 201, description = "tag added", body = Tag),
+            (status = 422, description = "tag name is empty or already exists")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn add_tag(
+    session: Session,
+    store: Store,
+    tag: NewTag,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("add tag");
+    save_tag(None, session, store, tag).await
+}
+
+#[utoipa::path(
+        put,
+        path = "tags/{id}",
+        request_body = NewTag,
+        params(
+            ("id" = i32, Path, description = "Tag unique id")
+        ),
+        responses(
+            (status = 201, description = "tag updated", body = Tag),
+            (status = 404, description = "tag not found"),
+            (status = 422, description = "tag name is empty or already exists")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn update_tag(
+    id: i32,
+    session: Session,
+    store: Store,
+    tag: NewTag,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("update tag");
+    save_tag(Some(id), session, store, tag).await
+}
+
+async fn save_tag(
+    id: Option<i32>,
+    session: Session,
+    store: Store,
+    tag: NewTag,
+) -> Result<warp::reply::WithStatus<warp::reply::Json>, warp::Rejection> {
+    let tag = tag.validate()?;
+    match store.save_tag(tag, id, session.account_id).await {
+        Ok(Some(res)) => Ok(warp::reply::with_status(json(&res), StatusCode::CREATED)),
+        Ok(None) => Ok(warp::reply::with_status(
+            json(&"Tag not found".to_string()),
+            StatusCode::NOT_FOUND,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[utoipa::path(
+        delete,
+        path = "tags/{id}",
+        params(
+            ("id" = i32, Path, description = "Tag unique id")
+        ),
+        responses(
+            (status = 200, description = "tag deleted and removed from the activities", body = i32),
+            (status = 404, description = "tag not found"),
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn delete_tag(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("delete tag");
+    match store.delete_tag(id, session.account_id).await {
+        Ok(true) => {
+            let answer = HashMap::from([("Tag deleted with id", id)]);
+            Ok(warp::reply::with_status(json(&answer), StatusCode::OK))
+        }
+        Ok(false) => Ok(warp::reply::with_status(
+            json(&"Tag not found".to_string()),
+            StatusCode::NOT_FOUND,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[utoipa::path(
+        get,
+        path = "activity/{id}/tags",
+        params(
+            ("id" = i32, Path, description = "Activity unique id")
+        ),
+        responses(
+            (status = 200, description = "Tags of activity", body = [Tag]),
+            (status = 404, description = "activity not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_activity_tags(
+    id: i32,
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering activity tags");
+    let account_id = session.account_id;
+
+    if !store.is_activity_owner(id, &account_id).await? {
+        return Ok(warp::reply::with_status(
+            json(&"Activity not found".to_string()),
+            StatusCode::NOT_FOUND,
+        ));
+    }
+    match store.get_activity_tags(id, account_id).await {
+        Ok(res) => Ok(warp::reply::with_status(json(&res), StatusCode::OK)),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[utoipa::path(
+        put,
+        path = "activity/{id}/tags",
+        request_body = [i32],
+        params(
+            ("id" = i32, Path, description = "Activity unique id")
+        ),
+        responses(
+            (status = 201, description = "tags of activity replaced", body = [Tag]),
+            (status = 404, description = "activity or tag not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn set_activity_tags(
+    id: i32,
+    session: Session,
+    store: Store,
+    tag_ids: Vec<i32>,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("set activity tags");
+    let account_id = session.account_id;
+
+    if !store.is_activity_owner(id, &account_id).await? {
+        return Ok(warp::reply::with_status(
+            json(&"Activity not found".to_string()),
+            StatusCode::NOT_FOUND,
+        ));
+    }
+    match store.set_activity_tags(id, tag_ids, account_id).await {
+        Ok(Some(res)) => Ok(warp::reply::with_status(json(&res), StatusCode::CREATED)),
+        Ok(None) => Ok(warp::reply::with_status(
+            json(&"Tag not found".to_string()),
+            StatusCode::NOT_FOUND,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[cfg(test)]
+mod test_tags {
+    use crate::routes::tags::{add_tag, delete_tag, set_activity_tags};
+    use crate::tests::helpers::{convert_to_string, create_postgres, get_session, prepare_store};
+    use crate::types::account::AccountID;
+    use crate::types::activities::ActivityFilter;
+    use crate::types::tags::{NewTag, Tag};
+    use testcontainers_modules::testcontainers::clients::Cli;
+    use warp::reply::Reply;
+
+    fn client_filter() -> ActivityFilter {
+        ActivityFilter {
+            tag: Some("client-a".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn client() -> NewTag {
+        NewTag {
+            name: "client-a".to_string(),
+            color: Some("#ff0000".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_can_filter_activities_by_tag() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        store.clone().add_test_acctivities().await;
+        let result = add_tag(get_session(1), store.clone(), client())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 201);
+        let body = warp::hyper::body::to_bytes(result.into_body())
+            .await
+            .unwrap();
+        let tag: Tag = serde_json::from_str(&convert_to_string(&body).await.unwrap()).unwrap();
+
+        let result = set_activity_tags(2, get_session(1), store.clone(), vec![tag.id])
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 201);
+
+        let res = store
+            .clone()
+            .get_activities(AccountID(1), None, None, None, client_filter())
+            .await
+            .unwrap();
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].id.0, 2);
+
+        delete_tag(tag.id, get_session(1), store.clone())
+            .await
+            .unwrap();
+        let res = store
+            .get_activities(AccountID(1), None, None, None, client_filter())
+            .await
+            .unwrap();
+        assert!(res.is_empty());
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_cant_use_not_owned_tag() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let tag = store
+            .clone()
+            .save_tag(client(), None, AccountID(2))
+            .await
+            .unwrap()
+            .unwrap();
+        let result = set_activity_tags(1, get_session(1), store.clone(), vec![tag.id])
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 404);
+        let result = delete_tag(tag.id, get_session(1), store.clone())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 404);
+    }
+}
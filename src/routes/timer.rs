@@ -1,8 +1,14 @@
-use crate::cache::CacheStore;
+use crate::cache::{CacheKey, CacheStore};
+use crate::events::EventBus;
 use crate::store::Store;
-use crate::types::account::Session;
+use crate::types::account::{AccountID, Session};
+use crate::types::activities::{ActivityId, ActivityStatus};
+use crate::types::entries::{NewTimeEntry, TimeEntry};
+use crate::types::events::Event;
+use crate::types::revisions::RevisionKind;
+use crate::types::timer::{StartedTimer, TimerOptions, TimerState, TimerStatus};
 use chrono::prelude::*;
-use tracing::info;
+use tracing::{error, info};
 use warp::http::StatusCode;
 use warp::reply::json;
 
@@ -10,11 +16,14 @@ use warp::reply::json;
         post,
         path = "timer/start/{activity_id}",
         responses(
-            (status = 200, description = "Timer started"),
-            (status = 404, description = "Not found")
+            (status = 200, description = "Timer started, other timers stopped in single active timer mode", body = StartedTimer),
+            (status = 404, description = "Not found"),
+            (status = 409, description = "Activity is cancelled or archived"),
+            (status = 422, description = "Pomodoro lengths are out of range")
         ),
         params(
-            ("activity_id" = i32, Path, description = "acctivity id")
+            ("activity_id" = i32, Path, description = "acctivity id"),
+            TimerOptions
         ),
         security(
             ("Authorization" = [])
@@ -25,31 +34,215 @@ pub async fn start(
     session: Session,
     store: Store,
     cache: CacheStore,
+    events: EventBus,
+    options: TimerOptions,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     let time = Utc::now();
     info!("start timer for: {}", id);
+    let pomodoro = options.pomodoro()?;
     let account_id = session.account_id;
-    if store
+    let key = CacheKey::timer(&account_id, id);
+    let activity = match store
         .clone()
-        .get_activity_by_id(account_id, id)
+        .get_activity_by_id(account_id.clone(), id)
         .await
-        .is_err()
     {
+        Ok(activity) => activity,
+        Err(_) => {
+            return Err(warp::reject::custom(
+                handle_errors::Error::MissingParameters,
+            ))
+        }
+    };
+
+    if let Ok(timer) = cache.clone().get_timer(&key).await {
+        let started = StartedTimer {
+            timer: TimerStatus::new(&activity, Some(&timer), time),
+            stopped: vec![],
+        };
+        return Ok(warp::reply::with_status(json(&started), StatusCode::OK));
+    }
+    let start_progress = activity.status != ActivityStatus::InProgress;
+    if start_progress {
+        activity.status.transition(ActivityStatus::InProgress)?;
+    }
+
+    let mut stopped = vec![];
+    if store
+        .clone()
+        .get_preferences(account_id.clone())
+        .await?
+        .single_active_timer
+    {
+        let running = match cache.clone().get_running_timers(&account_id).await {
+            Ok(running) => running,
+            Err(_) => return Err(warp::reject::not_found()),
+        };
+        for (activity_id, _) in running {
+            // one timer failing to stop doesn't keep the new one from starting
+            match book_timer(
+                &account_id,
+                activity_id,
+                time,
+                None,
+                store.clone(),
+                cache.clone(),
+                events.clone(),
+            )
+            .await
+            {
+                Ok(time_entry) => stopped.push(time_entry),
+                Err(e) => error!("Can't stop timer of {} with {:?}", activity_id, e),
+            }
+        }
+    }
+
+    let timer = match pomodoro {
+        Some(pomodoro) => TimerState::with_pomodoro(time, pomodoro),
+        None => TimerState::new(time),
+    };
+    match cache.clone().set_timer(&key, &timer).await {
+        Ok(_) => {
+            if start_progress {
+                if let Some(activity) = store
+                    .set_status(
+                        id,
+                        activity.status,
+                        ActivityStatus::InProgress,
+                        account_id.clone(),
+                    )
+                    .await?
+                {
+                    events.publish(&account_id, Event::ActivityUpdated { activity });
+                }
+            }
+            let started = StartedTimer {
+                timer: TimerStatus::new(&activity, Some(&timer), time),
+                stopped,
+            };
+            events.publish(
+                &account_id,
+                Event::TimerStarted {
+                    timer: started.timer.clone(),
+                },
+            );
+            Ok(warp::reply::with_status(json(&started), StatusCode::OK))
+        }
+        Err(_) => Err(warp::reject::not_found()),
+    }
+}
+
+#[utoipa::path(
+        post,
+        path = "timer/pause/{activity_id}",
+        responses(
+            (status = 200, description = "Timer paused", body = TimerStatus),
+            (status = 404, description = "Not found"),
+            (status = 409, description = "Timer already paused")
+        ),
+        params(
+            ("activity_id" = i32, Path, description = "acctivity id")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn pause(
+    id: i32,
+    session: Session,
+    store: Store,
+    cache: CacheStore,
+    events: EventBus,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("pause timer for: {}", id);
+    let account_id = session.account_id;
+    let key = CacheKey::timer(&account_id, id);
+    let activity = match store.get_activity_by_id(account_id.clone(), id).await {
+        Ok(activity) => activity,
+        Err(_) => return Err(warp::reject::not_found()),
+    };
+
+    let mut timer = match cache.clone().get_timer(&key).await {
+        Ok(timer) => timer,
+        Err(_) => return Err(warp::reject::not_found()),
+    };
+    if timer.is_paused() {
         return Err(warp::reject::custom(
-            handle_errors::Error::MissingParameters,
+            handle_errors::Error::TimerAlreadyPaused,
         ));
     }
 
-    if let Ok(get_value) = cache.clone().get_value(id.to_string()).await {
-        return Ok(warp::reply::with_status(
-            json(&(get_value / 1_000_000_000)),
-            StatusCode::OK,
-        ));
+    let time = Utc::now();
+    timer.pause(time);
+    match cache.clone().update_timer(&key, &timer).await {
+        Ok(true) => {
+            let status = TimerStatus::new(&activity, Some(&timer), time);
+            events.publish(
+                &account_id,
+                Event::TimerPaused {
+                    timer: status.clone(),
+                },
+            );
+            Ok(warp::reply::with_status(json(&status), StatusCode::OK))
+        }
+        // stopped while paused or resumed
+        Ok(false) | Err(_) => Err(warp::reject::not_found()),
     }
+}
 
-    match cache.clone().set_value(id.to_string(), time).await {
-        Ok(res) => Ok(warp::reply::with_status(json(&res), StatusCode::OK)),
-        Err(_) => Err(warp::reject::not_found()),
+#[utoipa::path(
+        post,
+        path = "timer/resume/{activity_id}",
+        responses(
+            (status = 200, description = "Timer resumed", body = TimerStatus),
+            (status = 404, description = "Not found"),
+            (status = 409, description = "Timer is not paused")
+        ),
+        params(
+            ("activity_id" = i32, Path, description = "acctivity id")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn resume(
+    id: i32,
+    session: Session,
+    store: Store,
+    cache: CacheStore,
+    events: EventBus,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("resume timer for: {}", id);
+    let account_id = session.account_id;
+    let key = CacheKey::timer(&account_id, id);
+    let activity = match store.get_activity_by_id(account_id.clone(), id).await {
+        Ok(activity) => activity,
+        Err(_) => return Err(warp::reject::not_found()),
+    };
+
+    let mut timer = match cache.clone().get_timer(&key).await {
+        Ok(timer) => timer,
+        Err(_) => return Err(warp::reject::not_found()),
+    };
+    if !timer.is_paused() {
+        return Err(warp::reject::custom(handle_errors::Error::TimerNotPaused));
+    }
+
+    let time = Utc::now();
+    timer.resume(time);
+    match cache.clone().update_timer(&key, &timer).await {
+        Ok(true) => {
+            let status = TimerStatus::new(&activity, Some(&timer), time);
+            events.publish(
+                &account_id,
+                Event::TimerResumed {
+                    timer: status.clone(),
+                },
+            );
+            Ok(warp::reply::with_status(json(&status), StatusCode::OK))
+        }
+        // stopped while paused or resumed
+        Ok(false) | Err(_) => Err(warp::reject::not_found()),
     }
 }
 
@@ -60,7 +253,7 @@ pub async fn start(
             ("activity_id" = i32, Path, description = "acctivity id")
         ),
         responses(
-            (status = 200, description = "Timer stop"),
+            (status = 200, description = "Timer stop", body = TimeEntry),
             (status = 404, description = "Not found"),
         ),
         security(
@@ -72,47 +265,173 @@ pub async fn stop(
     session: Session,
     store: Store,
     cache: CacheStore,
+    events: EventBus,
 ) -> Result<impl warp::Reply, warp::Rejection> {
     info!("stop timer for: {}", id);
-    let account_id = session.account_id;
+    let time_entry = book_timer(
+        &session.account_id,
+        id,
+        Utc::now(),
+        None,
+        store,
+        cache,
+        events,
+    )
+    .await?;
+    Ok(warp::reply::with_status(json(&time_entry), StatusCode::OK))
+}
 
-    let time_now = Utc::now();
-    let mut activity = store
+/// Removes the timer and books the time it tracked as an entry, time above
+/// `limit` seconds is dropped and the entry is marked for review.
+/// Pomodoro breaks are not booked. The timer is taken before booking, so
+/// concurrent stops and sweeps book it once.
+pub async fn book_timer(
+    account_id: &AccountID,
+    id: i32,
+    time: DateTime<Utc>,
+    limit: Option<i64>,
+    store: Store,
+    cache: CacheStore,
+    events: EventBus,
+) -> Result<TimeEntry, handle_errors::Error> {
+    let key = CacheKey::timer(account_id, id);
+    let timer = cache
         .clone()
-        .get_activity_by_id(account_id.clone(), id)
+        .take_timer(&key)
         .await
-        .unwrap();
+        .map_err(|_| handle_errors::Error::TimerNotFound)?;
 
-    match cache.clone().get_value(id.to_string()).await {
-        Ok(time) => {
-            let diff = ((time_now.timestamp_nanos_opt().unwrap() - time) / 1_000_000_000) as i32;
-            activity.time -= diff;
-            match store
-                .clone()
-                .update_activity(activity, id, account_id.clone())
-                .await
-            {
-                Ok(res) => res,
-                Err(e) => return Err(warp::reject::custom(e)),
-            };
+    let worked = timer.worked(time);
+    let duration = match limit {
+        Some(limit) => worked.min(limit.max(0)),
+        None => worked,
+    };
+    let entry = NewTimeEntry {
+        activity_id: ActivityId(id),
+        started_at: timer.started_at,
+        stopped_at: time,
+        duration: duration as i32,
+        needs_review: duration < worked,
+    };
+    let time_entry = match store
+        .book_time_entry(entry, account_id.clone(), RevisionKind::TimerStopped)
+        .await
+    {
+        Ok(time_entry) => time_entry,
+        Err(handle_errors::Error::DatabaseQueryError(sqlx::Error::RowNotFound)) => {
+            // the activity is gone, there is nothing to book the timer on
+            error!("Drop timer of missing activity {}", id);
+            return Err(handle_errors::Error::DatabaseQueryError(
+                sqlx::Error::RowNotFound,
+            ));
+        }
+        Err(e) => {
+            // nothing was booked, give the timer back to stop it later
+            if let Err(e) = cache.clone().set_timer(&key, &timer).await {
+                error!("Can't restore timer of {} with {:?}", id, e);
+            }
+            return Err(e);
         }
+    };
+
+    events.publish(
+        account_id,
+        Event::TimerStopped {
+            time_entry: time_entry.clone(),
+        },
+    );
+    Ok(time_entry)
+}
+
+#[utoipa::path(
+        get,
+        path = "timer/{activity_id}",
+        responses(
+            (status = 200, description = "Timer state of activity", body = TimerStatus),
+            (status = 404, description = "Not found")
+        ),
+        params(
+            ("activity_id" = i32, Path, description = "acctivity id")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn status(
+    id: i32,
+    session: Session,
+    store: Store,
+    cache: CacheStore,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("timer status for: {}", id);
+    let key = CacheKey::timer(&session.account_id, id);
+    let activity = match store.get_activity_by_id(session.account_id, id).await {
+        Ok(activity) => activity,
         Err(_) => return Err(warp::reject::not_found()),
     };
 
-    match cache.clone().delete_value(id.to_string()).await {
-        Ok(res) => Ok(warp::reply::with_status(json(&res), StatusCode::OK)),
-        Err(_) => Err(warp::reject::not_found()),
+    let timer = cache.clone().get_timer(&key).await.ok();
+    let status = TimerStatus::new(&activity, timer.as_ref(), Utc::now());
+    Ok(warp::reply::with_status(json(&status), StatusCode::OK))
+}
+
+#[utoipa::path(
+        get,
+        path = "timer",
+        responses(
+            (status = 200, description = "List running timers", body = [TimerStatus]),
+            (status = 404, description = "Not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn list(
+    session: Session,
+    store: Store,
+    cache: CacheStore,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("list running timers");
+    let account_id = session.account_id;
+    let timers = match cache.clone().get_running_timers(&account_id).await {
+        Ok(timers) => timers,
+        Err(_) => return Err(warp::reject::not_found()),
+    };
+
+    let time = Utc::now();
+    let mut res: Vec<TimerStatus> = vec![];
+    for (activity_id, timer) in timers {
+        if let Ok(activity) = store
+            .clone()
+            .get_activity_by_id(account_id.clone(), activity_id)
+            .await
+        {
+            res.push(TimerStatus::new(&activity, Some(&timer), time));
+        }
     }
+    Ok(warp::reply::with_status(json(&res), StatusCode::OK))
 }
 
 #[cfg(test)]
 mod test_timers {
-    use crate::{routes::timer::start, routes::timer::stop};
+    use crate::{
+        routes::timer::book_timer, routes::timer::list, routes::timer::pause,
+        routes::timer::resume, routes::timer::start, routes::timer::status, routes::timer::stop,
+    };
+    use chrono::{TimeDelta, Utc};
     use testcontainers::clients::Cli;
     use warp::reply::Reply;
 
+    use crate::cache::CacheKey;
+    use crate::events::EventBus;
     use crate::tests::helpers::{
-        create_postgres, create_redis, get_session, prepare_cache, prepare_store,
+        convert_to_string, create_postgres, create_redis, get_session, prepare_cache, prepare_store,
+    };
+    use crate::types::account::AccountID;
+    use crate::types::activities::ActivityStatus;
+    use crate::types::preferences::Preferences;
+    use crate::types::timer::{
+        PomodoroPhase, StartedTimer, TimerMode, TimerOptions, TimerState, TimerStatus,
     };
 
     #[tokio::test]
@@ -124,11 +443,51 @@ mod test_timers {
         let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
         store.clone().add_test_account(1).await;
         store.clone().add_test_acctivities().await;
-        let result = start(1, get_session(1), store.clone(), cache.clone())
-            .await
-            .unwrap()
-            .into_response();
+        let result = start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap()
+        .into_response();
         assert_eq!(result.status(), 200);
+        let activity = store.get_activity_by_id(AccountID(1), 1).await.unwrap();
+        assert_eq!(activity.status, ActivityStatus::InProgress);
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_cant_start_timer_of_archived_activity() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        for (from, to) in [
+            (ActivityStatus::Todo, ActivityStatus::Cancelled),
+            (ActivityStatus::Cancelled, ActivityStatus::Archived),
+        ] {
+            store
+                .clone()
+                .set_status(1, from, to, AccountID(1))
+                .await
+                .unwrap();
+        }
+        let result = start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await;
+        assert!(result.is_err());
     }
 
     #[tokio::test]
@@ -140,20 +499,237 @@ mod test_timers {
         let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
         store.clone().add_test_account(1).await;
         store.clone().add_test_acctivities().await;
-        let result = start(1, get_session(1), store.clone(), cache.clone())
+        let result = start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(result.status(), 200);
+        let result = stop(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(result.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn medium_test_if_user_press_start_after_started_return_key_value() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let result = start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(result.status(), 200);
+        let result = start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(result.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_can_pause_and_resume_timer() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+        let result = pause(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(result.status(), 200);
+        let result = pause(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+        )
+        .await;
+        assert!(result.is_err());
+        let result = resume(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(result.status(), 200);
+        let result = stop(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(result.status(), 200);
+    }
+
+    #[tokio::test]
+    async fn medium_test_timer_stopped_during_pause_is_not_recreated() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+        // pause reads the timer, then stop lands before the write
+        let key = CacheKey::timer(&AccountID(1), 1);
+        let mut timer = cache.clone().get_timer(&key).await.unwrap();
+        stop(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+        )
+        .await
+        .unwrap();
+        timer.pause(Utc::now());
+
+        assert!(!cache.clone().update_timer(&key, &timer).await.unwrap());
+        assert!(cache.clone().get_timer(&key).await.is_err());
+        let running = cache
+            .clone()
+            .get_running_timers(&AccountID(1))
+            .await
+            .unwrap();
+        assert!(running.is_empty());
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_cant_resume_running_timer() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+        let result = resume(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn medium_test_user_can_get_timer_status() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let result = status(1, get_session(1), store.clone(), cache.clone())
             .await
             .unwrap()
             .into_response();
         assert_eq!(result.status(), 200);
-        let result = stop(1, get_session(1), store.clone(), cache.clone())
+        start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+        let result = status(1, get_session(1), store.clone(), cache.clone())
             .await
             .unwrap()
             .into_response();
         assert_eq!(result.status(), 200);
+        let result = status(1, get_session(2), store.clone(), cache.clone()).await;
+        assert!(result.is_err());
     }
 
     #[tokio::test]
-    async fn medium_test_if_user_press_start_after_started_return_key_value() {
+    async fn medium_test_user_can_list_running_timers() {
         let docker = Cli::default();
         let node = docker.run(create_postgres());
         let redis = docker.run(create_redis());
@@ -161,15 +737,245 @@ mod test_timers {
         let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
         store.clone().add_test_account(1).await;
         store.clone().add_test_acctivities().await;
-        let result = start(1, get_session(1), store.clone(), cache.clone())
+        store.clone().add_test_acctivities().await;
+        start(
+            2,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+        let result = list(get_session(1), store.clone(), cache.clone())
             .await
             .unwrap()
             .into_response();
         assert_eq!(result.status(), 200);
-        let result = start(1, get_session(1), store.clone(), cache.clone())
+        let body = warp::hyper::body::to_bytes(result.into_body())
             .await
-            .unwrap()
-            .into_response();
+            .unwrap();
+        let timers: Vec<TimerStatus> =
+            serde_json::from_str(&convert_to_string(&body).await.unwrap()).unwrap();
+        assert_eq!(timers.len(), 1);
+        assert_eq!(timers[0].activity_id.0, 2);
+    }
+
+    #[tokio::test]
+    async fn medium_test_single_active_timer_mode_stops_other_timers() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        store.clone().add_test_acctivities().await;
+        store
+            .clone()
+            .update_preferences(
+                Preferences {
+                    single_active_timer: true,
+                    ..Default::default()
+                },
+                AccountID(1),
+            )
+            .await
+            .unwrap();
+        start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+        let result = start(
+            2,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap()
+        .into_response();
         assert_eq!(result.status(), 200);
+        let body = warp::hyper::body::to_bytes(result.into_body())
+            .await
+            .unwrap();
+        let started: StartedTimer =
+            serde_json::from_str(&convert_to_string(&body).await.unwrap()).unwrap();
+        assert_eq!(started.timer.activity_id.0, 2);
+        assert_eq!(started.stopped.len(), 1);
+        assert_eq!(started.stopped[0].activity_id.0, 1);
+        let running = cache
+            .clone()
+            .get_running_timers(&AccountID(1))
+            .await
+            .unwrap();
+        assert_eq!(running.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn medium_test_single_active_timer_mode_drops_timer_of_missing_activity() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        store.clone().add_test_acctivities().await;
+        store
+            .clone()
+            .update_preferences(
+                Preferences {
+                    single_active_timer: true,
+                    ..Default::default()
+                },
+                AccountID(1),
+            )
+            .await
+            .unwrap();
+        start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+        cache
+            .clone()
+            .set_timer(
+                &CacheKey::timer(&AccountID(1), 99),
+                &TimerState::new(Utc::now()),
+            )
+            .await
+            .unwrap();
+        let result = start(
+            2,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap()
+        .into_response();
+        assert_eq!(result.status(), 200);
+        let body = warp::hyper::body::to_bytes(result.into_body())
+            .await
+            .unwrap();
+        let started: StartedTimer =
+            serde_json::from_str(&convert_to_string(&body).await.unwrap()).unwrap();
+        assert_eq!(started.timer.activity_id.0, 2);
+        assert_eq!(started.stopped.len(), 1);
+        assert_eq!(started.stopped[0].activity_id.0, 1);
+        let running = cache
+            .clone()
+            .get_running_timers(&AccountID(1))
+            .await
+            .unwrap();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].0, 2);
+    }
+
+    #[tokio::test]
+    async fn medium_test_stop_timer_keeps_estimate_and_tracks_spent_time() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            TimerOptions::default(),
+        )
+        .await
+        .unwrap();
+        book_timer(
+            &AccountID(1),
+            1,
+            Utc::now() + TimeDelta::seconds(90),
+            None,
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+        )
+        .await
+        .unwrap();
+        let activity = store
+            .clone()
+            .get_activity_by_id(AccountID(1), 1)
+            .await
+            .unwrap();
+        assert_eq!(activity.time, 1);
+        assert!(activity.spent >= 90);
+        assert_eq!(activity.remaining, 0);
+        assert_eq!(activity.overtime, activity.spent - 1);
+        assert!(activity.is_overdue);
+    }
+
+    #[tokio::test]
+    async fn medium_test_pomodoro_timer_books_only_work_phases() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        let options = TimerOptions {
+            mode: Some(TimerMode::Pomodoro),
+            ..Default::default()
+        };
+        let result = start(
+            1,
+            get_session(1),
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+            options,
+        )
+        .await
+        .unwrap()
+        .into_response();
+        let body = warp::hyper::body::to_bytes(result.into_body())
+            .await
+            .unwrap();
+        let started: StartedTimer =
+            serde_json::from_str(&convert_to_string(&body).await.unwrap()).unwrap();
+        let pomodoro = started.timer.pomodoro.unwrap();
+        assert_eq!(pomodoro.phase, PomodoroPhase::Work);
+        assert_eq!(pomodoro.cycle, 1);
+
+        let time_entry = book_timer(
+            &AccountID(1),
+            1,
+            Utc::now() + TimeDelta::minutes(35),
+            None,
+            store.clone(),
+            cache.clone(),
+            EventBus::default(),
+        )
+        .await
+        .unwrap();
+        assert!(time_entry.duration >= 30 * 60);
+        assert!(time_entry.duration < 35 * 60);
     }
 }
@@ -0,0 +1,136 @@
+use crate::events::EventBus;
+use crate::store::Store;
+use crate::types::account::Session;
+use crate::types::activities::Activity;
+use crate::types::events::Event;
+use crate::types::trash::TrashedActivity;
+use tracing::{info, instrument};
+use warp::http::StatusCode;
+use warp::reply::json;
+
+#[instrument]
+#[utoipa::path(
+        get,
+        path = "trash",
+        responses(
+            (status = 200, description = "Deleted activities, the latest first", body = [TrashedActivity]),
+            (status = 404, description = "Rout not found")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn get_trash(
+    session: Session,
+    store: Store,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("quering trash");
+    let res: Vec<TrashedActivity> = match store.get_trash(session.account_id).await {
+        Ok(res) => res,
+        Err(e) => return Err(warp::reject::custom(e)),
+    };
+
+    Ok(warp::reply::json(&res))
+}
+
+#[utoipa::path(
+        post,
+        path = "trash/{id}/restore",
+        params(
+            ("id" = i32, Path, description = "Activity unique id")
+        ),
+        responses(
+            (status = 201, description = "activity restored from the trash", body = Activity),
+            (status = 404, description = "activity not found in the trash")
+        ),
+        security(
+            ("Authorization" = [])
+        )
+    )]
+pub async fn restore_activity(
+    id: i32,
+    session: Session,
+    store: Store,
+    events: EventBus,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    info!("restore activity");
+    let account_id = session.account_id;
+    match store.restore_activity(id, account_id.clone()).await {
+        Ok(Some(res)) => {
+            events.publish(
+                &account_id,
+                Event::ActivityCreated {
+                    activity: res.clone(),
+                },
+            );
+            Ok(warp::reply::with_status(json(&res), StatusCode::CREATED))
+        }
+        Ok(None) => Ok(warp::reply::with_status(
+            json(&"Activity not found".to_string()),
+            StatusCode::NOT_FOUND,
+        )),
+        Err(e) => Err(warp::reject::custom(e)),
+    }
+}
+
+#[cfg(test)]
+mod test_trash {
+    use crate::events::EventBus;
+    use crate::routes::activities::deleted_activities;
+    use crate::routes::trash::restore_activity;
+    use crate::tests::helpers::{
+        create_postgres, create_redis, get_session, prepare_cache, prepare_store,
+    };
+    use crate::types::account::AccountID;
+    use crate::types::activities::ActivityFilter;
+    use testcontainers_modules::testcontainers::clients::Cli;
+    use warp::reply::Reply;
+
+    #[tokio::test]
+    async fn medium_test_user_can_restore_deleted_activity() {
+        let docker = Cli::default();
+        let node = docker.run(create_postgres());
+        let redis = docker.run(create_redis());
+        let store = prepare_store(node.get_host_port_ipv4(5432)).await.unwrap();
+        let cache = prepare_cache(redis.get_host_port_ipv4(6379)).await.unwrap();
+        store.clone().add_test_account(1).await;
+        store.clone().add_test_acctivities().await;
+        store.clone().add_test_acctivities().await;
+        deleted_activities(1, get_session(1), store.clone(), cache, EventBus::default())
+            .await
+            .unwrap();
+
+        let res = store
+            .clone()
+            .get_activities(AccountID(1), None, None, None, ActivityFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(res.len(), 1);
+        assert!(store
+            .clone()
+            .get_activity_by_id(AccountID(1), 1)
+            .await
+            .is_err());
+        let trash = store.clone().get_trash(AccountID(1)).await.unwrap();
+        assert_eq!(trash.len(), 1);
+        assert_eq!(trash[0].activity.id.0, 1);
+
+        let result = restore_activity(1, get_session(2), store.clone(), EventBus::default())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 404);
+        let result = restore_activity(1, get_session(1), store.clone(), EventBus::default())
+            .await
+            .unwrap()
+            .into_response();
+        assert_eq!(result.status(), 201);
+        assert!(store
+            .clone()
+            .get_trash(AccountID(1))
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(store.get_activity_by_id(AccountID(1), 1).await.is_ok());
+    }
+}
@@ -0,0 +1,294 @@
+use chrono::prelude::*;
+use chrono::TimeDelta;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::store::Store;
+use crate::types::account::AccountID;
+use crate::types::activities::{Activity, ActivityId, ActivityStatus};
+use crate::types::availability::{Availability, RuleKind};
+use crate::types::plan::PlannedBlock;
+use crate::types::preferences::{local_to_utc, Preferences};
+use crate::types::recurrence::Day;
+
+/// Half open time range `[start, end)`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSchema)]
+pub struct Interval {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl Interval {
+    pub fn new(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Interval { start, end }
+    }
+
+    pub fn seconds(&self) -> i64 {
+        (self.end - self.start).num_seconds()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.end <= self.start
+    }
+}
+
+/// Union of the intervals, in order
+pub fn merge(mut intervals: Vec<Interval>) -> Vec<Interval> {
+    intervals.sort_by_key(|interval| interval.start);
+    let mut merged: Vec<Interval> = vec![];
+    for interval in intervals
+        .into_iter()
+        .filter(|interval| !interval.is_empty())
+    {
+        match merged.last_mut() {
+            Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+            _ => merged.push(interval),
+        }
+    }
+    merged
+}
+
+/// Scheduled activities and occurrences of recurring ones touching `[from, to)`
+pub async fn busy_intervals(
+    store: &Store,
+    account_id: &AccountID,
+    preferences: &Preferences,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<Interval>, handle_errors::Error> {
+    let mut busy: Vec<Interval> = store
+        .clone()
+        .get_scheduled_between(account_id.clone(), from, to)
+        .await?
+        .into_iter()
+        .filter_map(|activity| {
+            let scheduled_start = activity.scheduled_start?;
+            let scheduled_end = activity
+                .scheduled_end
+                .unwrap_or(scheduled_start + TimeDelta::seconds(activity.time as i64));
+            Some(Interval::new(scheduled_start, scheduled_end))
+        })
+        .collect();
+    for (activity, recurrence) in store
+        .clone()
+        .get_recurring_activities(account_id.clone())
+        .await?
+        .into_iter()
+        // closed activities don't take time, like the scheduled ones above
+        .filter(|(activity, _)| {
+            !matches!(
+                activity.status,
+                ActivityStatus::Cancelled | ActivityStatus::Archived
+            )
+        })
+    {
+        let duration = TimeDelta::seconds(activity.time as i64);
+        busy.extend(
+            recurrence
+                .occurrences(
+                    from - TimeDelta::days(1),
+                    to,
+                    &preferences.tz(),
+                    preferences.week_start(),
+                )
+                .into_iter()
+                .map(|starts_at| Interval::new(starts_at, starts_at + duration))
+                .filter(|interval| interval.end > from),
+        );
+    }
+    Ok(busy)
+}
+
+/// Parts of `window` not covered by any of the `busy` intervals, in order
+pub fn free_slots(window: Interval, mut busy: Vec<Interval>) -> Vec<Interval> {
+    busy.sort_by_key(|interval| interval.start);
+    let mut free = vec![];
+    let mut cursor = window.start;
+    for interval in busy {
+        if interval.end <= cursor || interval.start >= window.end {
+            continue;
+        }
+        if interval.start > cursor {
+            free.push(Interval::new(cursor, interval.start));
+        }
+        cursor = cursor.max(interval.end);
+    }
+    if cursor < window.end {
+        free.push(Interval::new(cursor, window.end));
+    }
+    free
+}
+
+/// Working time of the local `date`, rules are wall clock times of `tz`,
+/// breaks and time off are cut out
+pub fn working_intervals(availability: &Availability, date: NaiveDate, tz: &Tz) -> Vec<Interval> {
+    let day = Day::from(date.weekday());
+    let rules = availability.rules.iter().filter(|rule| rule.weekday == day);
+    let to_interval = |start: NaiveTime, end: NaiveTime| {
+        Interval::new(
+            local_to_utc(tz, date.and_time(start)),
+            local_to_utc(tz, date.and_time(end)),
+        )
+    };
+
+    let mut busy: Vec<Interval> = availability
+        .time_off
+        .iter()
+        .map(|time_off| Interval::new(time_off.starts_at, time_off.ends_at))
+        .collect();
+    busy.extend(
+        rules
+            .clone()
+            .filter(|rule| rule.kind == RuleKind::Break)
+            .map(|rule| to_interval(rule.start_time, rule.end_time)),
+    );
+
+    let mut work: Vec<Interval> = rules
+        .filter(|rule| rule.kind == RuleKind::Work)
+        .map(|rule| to_interval(rule.start_time, rule.end_time))
+        .collect();
+    work.sort_by_key(|interval| interval.start);
+    work.into_iter()
+        .flat_map(|window| free_slots(window, busy.clone()))
+        .collect()
+}
+
+/// Places every activity for its remaining time into the first free slot it fits,
+/// activities go in the given order. Returns the blocks and the activities left out.
+pub fn plan(
+    mut free: Vec<Interval>,
+    activities: &[Activity],
+) -> (Vec<PlannedBlock>, Vec<ActivityId>) {
+    let mut blocks = vec![];
+    let mut unplaced = vec![];
+    for activity in activities {
+        let duration = activity.remaining as i64;
+        if duration <= 0 {
+            continue;
+        }
+        match free.iter_mut().find(|slot| slot.seconds() >= duration) {
+            Some(slot) => {
+                let end = slot.start + TimeDelta::seconds(duration);
+                blocks.push(PlannedBlock {
+                    activity_id: activity.id.clone(),
+                    title: activity.title.clone(),
+                    scheduled_start: slot.start,
+                    scheduled_end: end,
+                });
+                slot.start = end;
+            }
+            None => unplaced.push(activity.id.clone()),
+        }
+        free.retain(|slot| !slot.is_empty());
+    }
+    (blocks, unplaced)
+}
+
+#[cfg(test)]
+mod test_schedule {
+    use super::*;
+    use crate::tests::helpers::test_activity;
+    use crate::types::availability::{AvailabilityRule, TimeOff};
+
+    fn at(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    #[test]
+    fn small_test_merge_joins_overlapping_intervals() {
+        let intervals = vec![
+            Interval::new(at("2026-10-19T12:00:00Z"), at("2026-10-19T13:00:00Z")),
+            Interval::new(at("2026-10-19T09:00:00Z"), at("2026-10-19T10:00:00Z")),
+            Interval::new(at("2026-10-19T12:30:00Z"), at("2026-10-19T14:00:00Z")),
+            Interval::new(at("2026-10-19T10:00:00Z"), at("2026-10-19T10:30:00Z")),
+        ];
+        assert_eq!(
+            merge(intervals),
+            vec![
+                Interval::new(at("2026-10-19T09:00:00Z"), at("2026-10-19T10:30:00Z")),
+                Interval::new(at("2026-10-19T12:00:00Z"), at("2026-10-19T14:00:00Z")),
+            ]
+        );
+    }
+
+    #[test]
+    fn small_test_free_slots_skip_busy_blocks() {
+        let window = Interval::new(at("2026-10-19T09:00:00Z"), at("2026-10-19T17:00:00Z"));
+        let busy = vec![
+            Interval::new(at("2026-10-19T12:00:00Z"), at("2026-10-19T13:00:00Z")),
+            Interval::new(at("2026-10-19T08:00:00Z"), at("2026-10-19T10:00:00Z")),
+            Interval::new(at("2026-10-19T12:30:00Z"), at("2026-10-19T13:30:00Z")),
+        ];
+        assert_eq!(
+            free_slots(window, busy),
+            vec![
+                Interval::new(at("2026-10-19T10:00:00Z"), at("2026-10-19T12:00:00Z")),
+                Interval::new(at("2026-10-19T13:30:00Z"), at("2026-10-19T17:00:00Z")),
+            ]
+        );
+    }
+
+    #[test]
+    fn small_test_working_intervals_cut_breaks_and_time_off() {
+        let rule = |id, kind, start: &str, end: &str| AvailabilityRule {
+            id,
+            weekday: Day::Mon,
+            kind,
+            start_time: start.parse().unwrap(),
+            end_time: end.parse().unwrap(),
+        };
+        let availability = Availability {
+            rules: vec![
+                rule(1, RuleKind::Work, "09:00:00", "17:00:00"),
+                rule(2, RuleKind::Break, "12:00:00", "13:00:00"),
+            ],
+            time_off: vec![TimeOff {
+                id: 1,
+                starts_at: at("2026-10-19T15:00:00Z"),
+                ends_at: at("2026-10-20T00:00:00Z"),
+                reason: None,
+            }],
+        };
+        // 2026-10-19 is a Monday
+        assert_eq!(
+            working_intervals(&availability, "2026-10-19".parse().unwrap(), &Tz::UTC),
+            vec![
+                Interval::new(at("2026-10-19T09:00:00Z"), at("2026-10-19T12:00:00Z")),
+                Interval::new(at("2026-10-19T13:00:00Z"), at("2026-10-19T15:00:00Z")),
+            ]
+        );
+        assert!(
+            working_intervals(&availability, "2026-10-20".parse().unwrap(), &Tz::UTC).is_empty()
+        );
+        // Working hours are local, 09:00 in New York is 13:00 UTC before the DST end
+        assert_eq!(
+            working_intervals(
+                &availability,
+                "2026-10-19".parse().unwrap(),
+                &Tz::America__New_York
+            )[0],
+            Interval::new(at("2026-10-19T13:00:00Z"), at("2026-10-19T15:00:00Z")),
+        );
+    }
+
+    #[test]
+    fn small_test_plan_uses_first_fitting_slot() {
+        let free = vec![
+            Interval::new(at("2026-10-19T09:00:00Z"), at("2026-10-19T10:00:00Z")),
+            Interval::new(at("2026-10-19T11:00:00Z"), at("2026-10-19T14:00:00Z")),
+        ];
+        let activities = vec![
+            test_activity(1, 7200, 0),
+            test_activity(2, 3600, 0),
+            test_activity(3, 4 * 3600, 0),
+        ];
+        let (blocks, unplaced) = plan(free, &activities);
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[0].activity_id, ActivityId(1));
+        assert_eq!(blocks[0].scheduled_start, at("2026-10-19T11:00:00Z"));
+        assert_eq!(blocks[1].activity_id, ActivityId(2));
+        assert_eq!(blocks[1].scheduled_start, at("2026-10-19T09:00:00Z"));
+        assert_eq!(unplaced, vec![ActivityId(3)]);
+    }
+}
@@ -1,13 +1,46 @@
+use chrono::prelude::*;
 use handle_errors::Error;
-use sqlx::postgres::{PgPool, PgPoolOptions, PgRow};
-use sqlx::Row;
+use sqlx::postgres::{PgConnection, PgPool, PgPoolOptions, PgRow, Postgres};
+use sqlx::{QueryBuilder, Row};
 
 use crate::types::{
     account::{Account, AccountID},
-    activities::{Activity, ActivityId, NewActivity},
+    activities::{
+        order_by, rank_after, rank_between, Activity, ActivityFilter, ActivityId, ActivityStatus,
+        NewActivity, SortKey, StatusChange,
+    },
+    availability::{
+        Availability, AvailabilityRule, NewAvailabilityRule, NewTimeOff, RuleKind, TimeOff,
+    },
+    entries::{NewTimeEntry, TimeEntry, TimeEntryId},
+    pagination::{Cursor, Direction},
+    plan::PlannedBlock,
+    preferences::{Preferences, TimeFormat},
+    projects::{NewProject, Project, ProjectId, Rollup},
+    recurrence::{Day, Frequency, Recurrence},
+    revisions::{ActivitySnapshot, Revision, RevisionKind},
+    search::SearchHit,
+    tags::{NewTag, Tag},
+    trash::TrashedActivity,
 };
 use tracing::error;
 
+/// Marks for `ts_headline`, the snippet keeps two short fragments of the content
+const SEARCH_HEADLINE: &str =
+    "StartSel=<mark>, StopSel=</mark>, MaxWords=20, MinWords=5, MaxFragments=2";
+
+/// Revisions of the `r` table, snapshots are read as text to parse them with serde
+const REVISION_COLUMNS: &str = "r.rev, r.kind, r.changed_by, r.changed_on, r.reverts, \
+    r.before::text AS before, r.after::text AS after";
+
+/// Sums of the project activities, joined to the `p` projects
+const PROJECT_ROLLUP: &str = r#"LATERAL (
+    SELECT COALESCE(SUM(a.time), 0)::BIGINT AS total_time,
+    COALESCE(SUM(a.spent), 0)::BIGINT AS total_spent,
+    COALESCE(SUM(GREATEST(a.time - a.spent, 0)), 0)::BIGINT AS total_remaining,
+    COALESCE(SUM(GREATEST(a.spent - a.time, 0)), 0)::BIGINT AS total_overtime
+    FROM activities a WHERE a.project_id = p.id AND a.deleted_at IS NULL) rollup"#;
+
 #[derive(Clone, Debug)]
 pub struct Store {
     pub connection: PgPool,
@@ -24,26 +57,46 @@ impl Store {
             connection: db_pool,
         })
     }
+    /// Walks from the cursor when there is one, a previous page comes in the list order too
     pub async fn get_activities(
         self,
         account_id: AccountID,
         limit: Option<i32>,
         offset: Option<i32>,
+        cursor: Option<&Cursor>,
+        filter: ActivityFilter,
     ) -> Result<Vec<Activity>, Error> {
-        match sqlx::query(r#"SELECT * from activities where account_id = $1 LIMIT $2 OFFSET $3"#)
-            .bind(account_id.0)
-            .bind(limit)
-            .bind(offset)
-            .map(|row: PgRow| Activity {
-                id: ActivityId(row.get("id")),
-                title: row.get("title"),
-                content: row.get("content"),
-                time: row.get("time"),
-            })
+        let sort = filter.sort.unwrap_or_default();
+        let mut keys = sort.keys(filter.order);
+        let backwards = cursor.is_some_and(|cursor| cursor.direction == Direction::Prev);
+        if backwards {
+            keys = keys.into_iter().map(SortKey::reversed).collect();
+        }
+        let mut query = QueryBuilder::new("SELECT * from activities WHERE ");
+        push_activity_filter(&mut query, &account_id, &filter);
+        if let Some(cursor) = cursor {
+            query.push(" AND ");
+            push_keyset(&mut query, &keys, &cursor.values());
+        }
+        query
+            .push(" ORDER BY ")
+            .push(order_by(&keys))
+            .push(" LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+        match query
+            .build()
+            .map(activity_from_row)
             .fetch_all(&self.connection)
             .await
         {
-            Ok(activities) => Ok(activities),
+            Ok(mut activities) => {
+                if backwards {
+                    activities.reverse();
+                }
+                Ok(activities)
+            }
             Err(e) => {
                 error!("Can't get activitues with {:?}", e);
                 Err(Error::DatabaseQueryError(e))
@@ -51,22 +104,78 @@ impl Store {
         }
     }
 
+    pub async fn count_activities(
+        &self,
+        account_id: AccountID,
+        filter: &ActivityFilter,
+    ) -> Result<i64, Error> {
+        let mut query = QueryBuilder::new("SELECT COUNT(*) AS total from activities WHERE ");
+        push_activity_filter(&mut query, &account_id, filter);
+        match query
+            .build()
+            .map(|row: PgRow| row.get("total"))
+            .fetch_one(&self.connection)
+            .await
+        {
+            Ok(total) => Ok(total),
+            Err(e) => {
+                error!("Can't count activities with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn search_activities(
+        self,
+        account_id: AccountID,
+        query: String,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<Vec<SearchHit>, Error> {
+        match sqlx::query(
+            r#"SELECT a.*, ts_rank(a.search, q) AS search_rank,
+            ts_headline('simple', a.title, q, $5 || ', HighlightAll=true') AS headline,
+            ts_headline('simple', a.content, q, $5) AS snippet
+            FROM activities a, websearch_to_tsquery('simple', $2) q
+            WHERE a.account_id = $1 AND a.deleted_at IS NULL AND a.search @@ q
+            ORDER BY search_rank DESC, a.id
+            LIMIT $3 OFFSET $4"#,
+        )
+        .bind(account_id.0)
+        .bind(query)
+        .bind(limit)
+        .bind(offset)
+        .bind(SEARCH_HEADLINE)
+        .map(|row: PgRow| SearchHit {
+            rank: row.get("search_rank"),
+            title: row.get("headline"),
+            snippet: row.get("snippet"),
+            activity: activity_from_row(row),
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(hits) => Ok(hits),
+            Err(e) => {
+                error!("Can't search activities with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
     pub async fn get_activity_by_id(
         self,
         account_id: AccountID,
         activity_id: i32,
     ) -> Result<Activity, Error> {
-        match sqlx::query(r#"SELECT * from activities where account_id = $1 and id = $2"#)
-            .bind(account_id.0)
-            .bind(activity_id)
-            .map(|row: PgRow| Activity {
-                id: ActivityId(row.get("id")),
-                title: row.get("title"),
-                content: row.get("content"),
-                time: row.get("time"),
-            })
-            .fetch_one(&self.connection)
-            .await
+        match sqlx::query(
+            r#"SELECT * from activities where account_id = $1 and id = $2 and deleted_at IS NULL"#,
+        )
+        .bind(account_id.0)
+        .bind(activity_id)
+        .map(activity_from_row)
+        .fetch_one(&self.connection)
+        .await
         {
             Ok(activity) => Ok(activity),
             Err(e) => {
@@ -84,77 +193,208 @@ impl Store {
         new_activity: NewActivity,
         account_id: AccountID,
     ) -> Result<Activity, Error> {
-        match sqlx::query(
-                r#"INSERT INTO activities (title, content, time, account_id) VALUES ($1, $2, $3, $4) RETURNING id, title, content, time"#,
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQueryError)?;
+
+        lock_ranks(&mut tx, &account_id).await?;
+        let rank = rank_after(&last_rank(&mut tx, &account_id).await?);
+        let activity = match sqlx::query(
+                r#"WITH added AS (
+                    INSERT INTO activities (title, content, time, account_id, scheduled_start, scheduled_end, due_at, project_id, parent_id, priority, rank)
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) RETURNING *
+                ), history AS (
+                    INSERT INTO activity_status_history (activity_id, to_status)
+                    SELECT id, status FROM added
+                )
+                SELECT * FROM added"#,
             )
             .bind(new_activity.title)
             .bind(new_activity.content)
-            .bind(new_activity.time)
+            .bind(new_activity.time.unwrap_or_default())
             .bind(account_id.0)
-            .map(|row: PgRow| Activity {
-                id: ActivityId(row.get("id")),
-                title: row.get("title"),
-                content: row.get("content"),
-                time: row.get("time"),
-            })
-            .fetch_one(&self.connection)
+            .bind(new_activity.scheduled_start)
+            .bind(new_activity.scheduled_end)
+            .bind(new_activity.due_at)
+            .bind(new_activity.project_id.map(|id| id.0))
+            .bind(new_activity.parent_id.map(|id| id.0))
+            .bind(new_activity.priority.unwrap_or_default())
+            .bind(rank)
+            .map(activity_from_row)
+            .fetch_one(&mut *tx)
             .await
             {
-                Ok(activity) => Ok(activity),
+                Ok(activity) => activity,
 
                 Err(e) => {
                     error!("Can't add activity with {:?}", e);
-                    Err(Error::DatabaseQueryError(e))
+                    return Err(Error::DatabaseQueryError(e));
                 }
-            }
+            };
+
+        tx.commit().await.map_err(Error::DatabaseQueryError)?;
+        Ok(activity)
     }
+    /// Saves the edit and records it in the history, `reverts` names the undone revision
     pub async fn update_activity(
         self,
         activity: Activity,
         activity_id: i32,
         account_id: AccountID,
+        reverts: Option<i32>,
     ) -> Result<Activity, Error> {
-        match sqlx::query(
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQueryError)?;
+
+        let before = lock_activity(&mut tx, activity_id, &account_id, false)
+            .await?
+            .ok_or(Error::DatabaseQueryError(sqlx::Error::RowNotFound))?;
+
+        let activity = match sqlx::query(
             r#"UPDATE activities
-            SET title = $1, content = $2, time = $3
-            WHERE id = $4 and account_id = $5
-            RETURNING id, title, content, time"#,
+            SET title = $1, content = $2, time = $3,
+            scheduled_start = $6, scheduled_end = $7, due_at = $8,
+            project_id = $9, parent_id = $10, priority = $11, updated_on = NOW()
+            WHERE id = $4 and account_id = $5 and deleted_at IS NULL
+            RETURNING *"#,
         )
         .bind(activity.title)
         .bind(activity.content)
         .bind(activity.time)
         .bind(activity_id)
         .bind(account_id.0)
-        .map(|row: PgRow| Activity {
-            id: ActivityId(row.get("id")),
-            title: row.get("title"),
-            content: row.get("content"),
-            time: row.get("time"),
-        })
-        .fetch_one(&self.connection)
+        .bind(activity.scheduled_start)
+        .bind(activity.scheduled_end)
+        .bind(activity.due_at)
+        .bind(activity.project_id.map(|id| id.0))
+        .bind(activity.parent_id.map(|id| id.0))
+        .bind(activity.priority)
+        .map(activity_from_row)
+        .fetch_one(&mut *tx)
         .await
         {
-            Ok(activity) => Ok(activity),
+            Ok(activity) => activity,
             Err(e) => {
                 error!("Can't update activity with {:?}", e);
-                Err(Error::DatabaseQueryError(e))
+                return Err(Error::DatabaseQueryError(e));
             }
-        }
+        };
+
+        let kind = match reverts {
+            Some(_) => RevisionKind::Revert,
+            None => RevisionKind::Update,
+        };
+        add_revision(&mut tx, &account_id, kind, reverts, &before, &activity).await?;
+
+        tx.commit().await.map_err(Error::DatabaseQueryError)?;
+        Ok(activity)
     }
+    /// Moves the activity to the trash, `false` when it's already there
     pub async fn delete_activity(
         &self,
         activity_id: i32,
         account_id: AccountID,
     ) -> Result<bool, Error> {
-        match sqlx::query(r#"DELETE FROM activities WHERE id = $1 and account_id = $2"#)
-            .bind(activity_id)
-            .bind(account_id.0)
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQueryError)?;
+
+        let Some(activity) = lock_activity(&mut tx, activity_id, &account_id, false).await? else {
+            return Ok(false);
+        };
+
+        if let Err(e) = sqlx::query(
+            r#"UPDATE activities SET deleted_at = NOW()
+            WHERE id = $1 and account_id = $2"#,
+        )
+        .bind(activity_id)
+        .bind(account_id.0)
+        .execute(&mut *tx)
+        .await
+        {
+            error!("Can't delete activity with {:?}", e);
+            return Err(Error::DatabaseQueryError(e));
+        }
+
+        add_revision(
+            &mut tx,
+            &account_id,
+            RevisionKind::Delete,
+            None,
+            &activity,
+            &activity,
+        )
+        .await?;
+
+        tx.commit().await.map_err(Error::DatabaseQueryError)?;
+        Ok(true)
+    }
+
+    /// Deleted activities of the account, the latest first
+    pub async fn get_trash(self, account_id: AccountID) -> Result<Vec<TrashedActivity>, Error> {
+        match sqlx::query(
+            r#"SELECT * from activities
+            where account_id = $1 and deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC, id"#,
+        )
+        .bind(account_id.0)
+        .map(|row: PgRow| TrashedActivity {
+            deleted_at: row.get("deleted_at"),
+            activity: activity_from_row(row),
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(activities) => Ok(activities),
+            Err(e) => {
+                error!("Can't get trash with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Takes the activity out of the trash, `None` when it isn't there
+    pub async fn restore_activity(
+        self,
+        activity_id: i32,
+        account_id: AccountID,
+    ) -> Result<Option<Activity>, Error> {
+        match sqlx::query(
+            r#"UPDATE activities SET deleted_at = NULL, updated_on = NOW()
+            WHERE id = $1 and account_id = $2 and deleted_at IS NOT NULL
+            RETURNING *"#,
+        )
+        .bind(activity_id)
+        .bind(account_id.0)
+        .map(activity_from_row)
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(activity) => Ok(activity),
+            Err(e) => {
+                error!("Can't restore activity with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Removes for good the activities deleted before `deleted_before`, of all the accounts
+    pub async fn purge_trash(&self, deleted_before: DateTime<Utc>) -> Result<u64, Error> {
+        match sqlx::query(r#"DELETE FROM activities WHERE deleted_at < $1"#)
+            .bind(deleted_before)
             .execute(&self.connection)
             .await
         {
-            Ok(_) => Ok(true),
+            Ok(res) => Ok(res.rows_affected()),
             Err(e) => {
-                error!("Can't delete activity with {:?}", e);
+                error!("Can't purge trash with {:?}", e);
                 Err(Error::DatabaseQueryError(e))
             }
         }
@@ -207,17 +447,1547 @@ impl Store {
         activity_id: i32,
         account_id: &AccountID,
     ) -> Result<bool, Error> {
-        match sqlx::query("SELECT * from activities where id = $1 and account_id = $2")
-            .bind(activity_id)
+        match sqlx::query(
+            "SELECT * from activities where id = $1 and account_id = $2 and deleted_at IS NULL",
+        )
+        .bind(activity_id)
+        .bind(account_id.0)
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(activity) => Ok(activity.is_some()),
+            Err(e) => {
+                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// True when the activity of the account is in the trash
+    pub async fn is_trashed(
+        &self,
+        activity_id: i32,
+        account_id: &AccountID,
+    ) -> Result<bool, Error> {
+        match sqlx::query(
+            "SELECT id from activities where id = $1 and account_id = $2 and deleted_at IS NOT NULL",
+        )
+        .bind(activity_id)
+        .bind(account_id.0)
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(activity) => Ok(activity.is_some()),
+            Err(e) => {
+                error!("Can't check trashed activity with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Books the entry and records it in the activity history as `kind`. Entries added
+    /// by hand can't overlap, timers are booked on trashed activities too.
+    pub async fn book_time_entry(
+        self,
+        entry: NewTimeEntry,
+        account_id: AccountID,
+        kind: RevisionKind,
+    ) -> Result<TimeEntry, Error> {
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQueryError)?;
+
+        // entries of the activity are checked and written under its row lock
+        let before = lock_activity(&mut tx, entry.activity_id.0, &account_id, true)
+            .await?
+            .ok_or(Error::DatabaseQueryError(sqlx::Error::RowNotFound))?;
+        if kind == RevisionKind::EntryAdded
+            && has_overlapping_entry(&mut tx, &entry, &account_id, None).await?
+        {
+            return Err(Error::OverlappingTimeEntry);
+        }
+
+        add_spent(&mut tx, &before, &account_id, kind, entry.duration).await?;
+
+        let time_entry = match sqlx::query(
+            r#"INSERT INTO time_entries (activity_id, account_id, started_at, stopped_at, duration, needs_review)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *"#,
+        )
+        .bind(entry.activity_id.0)
+        .bind(account_id.0)
+        .bind(entry.started_at)
+        .bind(entry.stopped_at)
+        .bind(entry.duration)
+        .bind(entry.needs_review)
+        .map(time_entry_from_row)
+        .fetch_one(&mut *tx)
+        .await
+        {
+            Ok(time_entry) => time_entry,
+            Err(e) => {
+                error!("Can't add time entry with {:?}", e);
+                return Err(Error::DatabaseQueryError(e));
+            }
+        };
+
+        tx.commit().await.map_err(Error::DatabaseQueryError)?;
+        Ok(time_entry)
+    }
+
+    pub async fn update_time_entry(
+        self,
+        entry_id: i32,
+        entry: NewTimeEntry,
+        account_id: AccountID,
+    ) -> Result<TimeEntry, Error> {
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQueryError)?;
+
+        let before = lock_activity(&mut tx, entry.activity_id.0, &account_id, true)
+            .await?
+            .ok_or(Error::TimeSpentNotFound)?;
+        let old_duration: i32 = match sqlx::query(
+            r#"SELECT duration from time_entries
+            WHERE id = $1 and activity_id = $2 and account_id = $3
+            FOR UPDATE"#,
+        )
+        .bind(entry_id)
+        .bind(entry.activity_id.0)
+        .bind(account_id.0)
+        .map(|row: PgRow| row.get("duration"))
+        .fetch_optional(&mut *tx)
+        .await
+        {
+            Ok(Some(duration)) => duration,
+            Ok(None) => return Err(Error::TimeSpentNotFound),
+            Err(e) => {
+                error!("Can't get time entry with {:?}", e);
+                return Err(Error::DatabaseQueryError(e));
+            }
+        };
+        if has_overlapping_entry(&mut tx, &entry, &account_id, Some(entry_id)).await? {
+            return Err(Error::OverlappingTimeEntry);
+        }
+
+        add_spent(
+            &mut tx,
+            &before,
+            &account_id,
+            RevisionKind::EntryUpdated,
+            entry.duration - old_duration,
+        )
+        .await?;
+
+        let time_entry = match sqlx::query(
+            r#"UPDATE time_entries
+            SET started_at = $1, stopped_at = $2, duration = $3, needs_review = $4
+            WHERE id = $5
+            RETURNING *"#,
+        )
+        .bind(entry.started_at)
+        .bind(entry.stopped_at)
+        .bind(entry.duration)
+        .bind(entry.needs_review)
+        .bind(entry_id)
+        .map(time_entry_from_row)
+        .fetch_one(&mut *tx)
+        .await
+        {
+            Ok(time_entry) => time_entry,
+            Err(e) => {
+                error!("Can't update time entry with {:?}", e);
+                return Err(Error::DatabaseQueryError(e));
+            }
+        };
+
+        tx.commit().await.map_err(Error::DatabaseQueryError)?;
+        Ok(time_entry)
+    }
+
+    pub async fn delete_time_entry(
+        self,
+        entry_id: i32,
+        activity_id: i32,
+        account_id: AccountID,
+    ) -> Result<bool, Error> {
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQueryError)?;
+
+        let before = lock_activity(&mut tx, activity_id, &account_id, true)
+            .await?
+            .ok_or(Error::TimeSpentNotFound)?;
+        let duration: i32 = match sqlx::query(
+            r#"DELETE FROM time_entries
+            WHERE id = $1 and activity_id = $2 and account_id = $3
+            RETURNING duration"#,
+        )
+        .bind(entry_id)
+        .bind(activity_id)
+        .bind(account_id.0)
+        .map(|row: PgRow| row.get("duration"))
+        .fetch_optional(&mut *tx)
+        .await
+        {
+            Ok(Some(duration)) => duration,
+            Ok(None) => return Err(Error::TimeSpentNotFound),
+            Err(e) => {
+                error!("Can't delete time entry with {:?}", e);
+                return Err(Error::DatabaseQueryError(e));
+            }
+        };
+
+        add_spent(
+            &mut tx,
+            &before,
+            &account_id,
+            RevisionKind::EntryDeleted,
+            -duration,
+        )
+        .await?;
+
+        tx.commit().await.map_err(Error::DatabaseQueryError)?;
+        Ok(true)
+    }
+
+    pub async fn get_time_entries(
+        self,
+        account_id: AccountID,
+        activity_id: i32,
+    ) -> Result<Vec<TimeEntry>, Error> {
+        match sqlx::query(
+            r#"SELECT * from time_entries
+            WHERE activity_id = $1 and account_id = $2
+            ORDER BY started_at"#,
+        )
+        .bind(activity_id)
+        .bind(account_id.0)
+        .map(time_entry_from_row)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(entries) => Ok(entries),
+            Err(e) => {
+                error!(
+                    "Can't get time entries for activity {:?}, with error: {:?}",
+                    activity_id, e
+                );
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn get_preferences(self, account_id: AccountID) -> Result<Preferences, Error> {
+        match sqlx::query(r#"SELECT * from account_preferences where account_id = $1"#)
             .bind(account_id.0)
+            .map(|row: PgRow| preferences_from_row(&row))
             .fetch_optional(&self.connection)
             .await
         {
-            Ok(activity) => Ok(activity.is_some()),
+            Ok(preferences) => Ok(preferences.unwrap_or_default()),
             Err(e) => {
-                tracing::event!(tracing::Level::ERROR, "{:?}", e);
+                error!("Can't get preferences with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn update_preferences(
+        self,
+        preferences: Preferences,
+        account_id: AccountID,
+    ) -> Result<Preferences, Error> {
+        match sqlx::query(
+            r#"INSERT INTO account_preferences
+            (account_id, single_active_timer, timezone, week_start, default_activity_duration, time_format)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (account_id) DO UPDATE SET
+            single_active_timer = $2, timezone = $3, week_start = $4,
+            default_activity_duration = $5, time_format = $6
+            RETURNING *"#,
+        )
+        .bind(account_id.0)
+        .bind(preferences.single_active_timer)
+        .bind(preferences.timezone)
+        .bind(Weekday::from(preferences.week_start).num_days_from_monday() as i16)
+        .bind(preferences.default_activity_duration)
+        .bind(preferences.time_format.as_str())
+        .map(|row: PgRow| preferences_from_row(&row))
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(preferences) => Ok(preferences),
+            Err(e) => {
+                error!("Can't update preferences with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn set_recurrence(
+        self,
+        recurrence: Recurrence,
+        account_id: AccountID,
+    ) -> Result<Recurrence, Error> {
+        let by_weekday: Vec<i16> = recurrence
+            .by_weekday
+            .iter()
+            .map(|day| Weekday::from(*day).num_days_from_monday() as i16)
+            .collect();
+        match sqlx::query(
+            r#"INSERT INTO activity_recurrences
+            (activity_id, account_id, frequency, repeat_interval, by_weekday, starts_at, until, count, exceptions)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (activity_id) DO UPDATE SET
+            frequency = $3, repeat_interval = $4, by_weekday = $5, starts_at = $6,
+            until = $7, count = $8, exceptions = $9
+            RETURNING *"#,
+        )
+        .bind(recurrence.activity_id.0)
+        .bind(account_id.0)
+        .bind(recurrence.frequency.as_str())
+        .bind(recurrence.interval)
+        .bind(by_weekday)
+        .bind(recurrence.starts_at)
+        .bind(recurrence.until)
+        .bind(recurrence.count)
+        .bind(recurrence.exceptions)
+        .map(|row: PgRow| recurrence_from_row(&row))
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(recurrence) => Ok(recurrence),
+            Err(e) => {
+                error!("Can't set recurrence with {:?}", e);
                 Err(Error::DatabaseQueryError(e))
             }
         }
     }
+
+    pub async fn get_recurrence(
+        self,
+        activity_id: i32,
+        account_id: AccountID,
+    ) -> Result<Option<Recurrence>, Error> {
+        match sqlx::query(
+            r#"SELECT * from activity_recurrences where activity_id = $1 and account_id = $2"#,
+        )
+        .bind(activity_id)
+        .bind(account_id.0)
+        .map(|row: PgRow| recurrence_from_row(&row))
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(recurrence) => Ok(recurrence),
+            Err(e) => {
+                error!("Can't get recurrence with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn delete_recurrence(
+        self,
+        activity_id: i32,
+        account_id: AccountID,
+    ) -> Result<bool, Error> {
+        match sqlx::query(
+            r#"DELETE FROM activity_recurrences where activity_id = $1 and account_id = $2"#,
+        )
+        .bind(activity_id)
+        .bind(account_id.0)
+        .execute(&self.connection)
+        .await
+        {
+            Ok(res) => Ok(res.rows_affected() > 0),
+            Err(e) => {
+                error!("Can't delete recurrence with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Recurring activities of the account with their rules
+    pub async fn get_recurring_activities(
+        self,
+        account_id: AccountID,
+    ) -> Result<Vec<(Activity, Recurrence)>, Error> {
+        match sqlx::query(
+            r#"SELECT a.*, r.activity_id, r.frequency, r.repeat_interval, r.by_weekday,
+            r.starts_at, r.until, r.count, r.exceptions
+            from activities a JOIN activity_recurrences r ON r.activity_id = a.id
+            where a.account_id = $1 and a.deleted_at IS NULL"#,
+        )
+        .bind(account_id.0)
+        .map(|row: PgRow| {
+            let recurrence = recurrence_from_row(&row);
+            (activity_from_row(row), recurrence)
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(activities) => Ok(activities),
+            Err(e) => {
+                error!("Can't get recurring activities with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Activities with a schedule overlapping `[from, to)`, without an end they last the estimate
+    pub async fn get_scheduled_between(
+        self,
+        account_id: AccountID,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Activity>, Error> {
+        match sqlx::query(
+            r#"SELECT * from activities
+            WHERE account_id = $1 and deleted_at IS NULL and scheduled_start < $3
+            and status NOT IN ('cancelled', 'archived')
+            and COALESCE(scheduled_end, scheduled_start + make_interval(secs => time)) > $2
+            ORDER BY scheduled_start"#,
+        )
+        .bind(account_id.0)
+        .bind(from)
+        .bind(to)
+        .map(activity_from_row)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(activities) => Ok(activities),
+            Err(e) => {
+                error!("Can't get scheduled activities with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Open, not scheduled and not recurring activities with time left,
+    /// the closest deadline first, then the most urgent
+    pub async fn get_unscheduled(self, account_id: AccountID) -> Result<Vec<Activity>, Error> {
+        match sqlx::query(
+            r#"SELECT * from activities
+            WHERE account_id = $1 and deleted_at IS NULL and scheduled_start IS NULL and spent < time
+            and status IN ('todo', 'in_progress')
+            and NOT EXISTS (SELECT 1 from activity_recurrences r WHERE r.activity_id = activities.id)
+            ORDER BY due_at ASC NULLS LAST, priority DESC, rank, id"#,
+        )
+        .bind(account_id.0)
+        .map(activity_from_row)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(activities) => Ok(activities),
+            Err(e) => {
+                error!("Can't get unscheduled activities with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Saves all the blocks or none of them
+    pub async fn set_schedules(
+        self,
+        blocks: Vec<PlannedBlock>,
+        account_id: AccountID,
+    ) -> Result<Vec<Activity>, Error> {
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQueryError)?;
+        let mut activities = vec![];
+        for block in blocks {
+            match sqlx::query(
+                r#"UPDATE activities
+                SET scheduled_start = $1, scheduled_end = $2, updated_on = NOW()
+                WHERE id = $3 and account_id = $4 and deleted_at IS NULL
+                RETURNING *"#,
+            )
+            .bind(block.scheduled_start)
+            .bind(block.scheduled_end)
+            .bind(block.activity_id.0)
+            .bind(account_id.0)
+            .map(activity_from_row)
+            .fetch_one(&mut *tx)
+            .await
+            {
+                Ok(activity) => activities.push(activity),
+                Err(e) => {
+                    error!("Can't set schedule with {:?}", e);
+                    return Err(Error::DatabaseQueryError(e));
+                }
+            }
+        }
+        tx.commit().await.map_err(Error::DatabaseQueryError)?;
+        Ok(activities)
+    }
+
+    pub async fn get_availability(self, account_id: AccountID) -> Result<Availability, Error> {
+        let rules = match sqlx::query(
+            r#"SELECT * from availability_rules where account_id = $1
+            ORDER BY weekday, start_time"#,
+        )
+        .bind(account_id.0)
+        .map(|row: PgRow| availability_rule_from_row(&row))
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(rules) => rules,
+            Err(e) => {
+                error!("Can't get availability rules with {:?}", e);
+                return Err(Error::DatabaseQueryError(e));
+            }
+        };
+        match sqlx::query(r#"SELECT * from time_off where account_id = $1 ORDER BY starts_at"#)
+            .bind(account_id.0)
+            .map(|row: PgRow| time_off_from_row(&row))
+            .fetch_all(&self.connection)
+            .await
+        {
+            Ok(time_off) => Ok(Availability { rules, time_off }),
+            Err(e) => {
+                error!("Can't get time off with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Adds the rule, with `rule_id` replaces the owned rule. Empty when there is nothing to replace.
+    pub async fn save_availability_rule(
+        self,
+        rule: NewAvailabilityRule,
+        rule_id: Option<i32>,
+        account_id: AccountID,
+    ) -> Result<Option<AvailabilityRule>, Error> {
+        let query = match rule_id {
+            Some(_) => {
+                r#"UPDATE availability_rules
+                SET weekday = $3, kind = $4, start_time = $5, end_time = $6
+                WHERE id = $1 and account_id = $2
+                RETURNING *"#
+            }
+            None => {
+                r#"INSERT INTO availability_rules (account_id, weekday, kind, start_time, end_time)
+                VALUES ($2, $3, $4, $5, $6)
+                RETURNING *"#
+            }
+        };
+        match sqlx::query(query)
+            .bind(rule_id)
+            .bind(account_id.0)
+            .bind(Weekday::from(rule.weekday).num_days_from_monday() as i16)
+            .bind(rule.kind.as_str())
+            .bind(rule.start_time)
+            .bind(rule.end_time)
+            .map(|row: PgRow| availability_rule_from_row(&row))
+            .fetch_optional(&self.connection)
+            .await
+        {
+            Ok(rule) => Ok(rule),
+            Err(e) => {
+                error!("Can't save availability rule with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn delete_availability_rule(
+        self,
+        rule_id: i32,
+        account_id: AccountID,
+    ) -> Result<bool, Error> {
+        match sqlx::query(r#"DELETE FROM availability_rules WHERE id = $1 and account_id = $2"#)
+            .bind(rule_id)
+            .bind(account_id.0)
+            .execute(&self.connection)
+            .await
+        {
+            Ok(res) => Ok(res.rows_affected() > 0),
+            Err(e) => {
+                error!("Can't delete availability rule with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Adds the time off, with `time_off_id` replaces the owned one. Empty when there is nothing to replace.
+    pub async fn save_time_off(
+        self,
+        time_off: NewTimeOff,
+        time_off_id: Option<i32>,
+        account_id: AccountID,
+    ) -> Result<Option<TimeOff>, Error> {
+        let query = match time_off_id {
+            Some(_) => {
+                r#"UPDATE time_off
+                SET starts_at = $3, ends_at = $4, reason = $5
+                WHERE id = $1 and account_id = $2
+                RETURNING *"#
+            }
+            None => {
+                r#"INSERT INTO time_off (account_id, starts_at, ends_at, reason)
+                VALUES ($2, $3, $4, $5)
+                RETURNING *"#
+            }
+        };
+        match sqlx::query(query)
+            .bind(time_off_id)
+            .bind(account_id.0)
+            .bind(time_off.starts_at)
+            .bind(time_off.ends_at)
+            .bind(time_off.reason)
+            .map(|row: PgRow| time_off_from_row(&row))
+            .fetch_optional(&self.connection)
+            .await
+        {
+            Ok(time_off) => Ok(time_off),
+            Err(e) => {
+                error!("Can't save time off with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn delete_time_off(
+        self,
+        time_off_id: i32,
+        account_id: AccountID,
+    ) -> Result<bool, Error> {
+        match sqlx::query(r#"DELETE FROM time_off WHERE id = $1 and account_id = $2"#)
+            .bind(time_off_id)
+            .bind(account_id.0)
+            .execute(&self.connection)
+            .await
+        {
+            Ok(res) => Ok(res.rows_affected() > 0),
+            Err(e) => {
+                error!("Can't delete time off with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn get_tags(self, account_id: AccountID) -> Result<Vec<Tag>, Error> {
+        match sqlx::query(r#"SELECT * from tags where account_id = $1 ORDER BY name"#)
+            .bind(account_id.0)
+            .map(|row: PgRow| tag_from_row(&row))
+            .fetch_all(&self.connection)
+            .await
+        {
+            Ok(tags) => Ok(tags),
+            Err(e) => {
+                error!("Can't get tags with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Adds a tag, or updates the tag with `id` when it's owned by the account
+    pub async fn save_tag(
+        self,
+        tag: NewTag,
+        id: Option<i32>,
+        account_id: AccountID,
+    ) -> Result<Option<Tag>, Error> {
+        let query = match id {
+            Some(_) => {
+                r#"UPDATE tags SET name = $1, color = $2
+                WHERE id = $3 and account_id = $4
+                RETURNING *"#
+            }
+            None => {
+                r#"INSERT INTO tags (name, color, account_id)
+                VALUES ($1, $2, $4)
+                RETURNING *"#
+            }
+        };
+        match sqlx::query(query)
+            .bind(tag.name)
+            .bind(tag.color)
+            .bind(id)
+            .bind(account_id.0)
+            .map(|row: PgRow| tag_from_row(&row))
+            .fetch_optional(&self.connection)
+            .await
+        {
+            Ok(tag) => Ok(tag),
+            Err(e) => {
+                error!("Can't save tag with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn delete_tag(self, id: i32, account_id: AccountID) -> Result<bool, Error> {
+        match sqlx::query(r#"DELETE FROM tags WHERE id = $1 and account_id = $2"#)
+            .bind(id)
+            .bind(account_id.0)
+            .execute(&self.connection)
+            .await
+        {
+            Ok(res) => Ok(res.rows_affected() > 0),
+            Err(e) => {
+                error!("Can't delete tag with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn get_activity_tags(
+        self,
+        activity_id: i32,
+        account_id: AccountID,
+    ) -> Result<Vec<Tag>, Error> {
+        match sqlx::query(
+            r#"SELECT tags.* FROM tags
+            JOIN activity_tags ON activity_tags.tag_id = tags.id
+            WHERE activity_tags.activity_id = $1 and tags.account_id = $2
+            ORDER BY tags.name"#,
+        )
+        .bind(activity_id)
+        .bind(account_id.0)
+        .map(|row: PgRow| tag_from_row(&row))
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(tags) => Ok(tags),
+            Err(e) => {
+                error!("Can't get activity tags with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Replaces the tags of the activity, empty when one of the tags isn't owned by the account
+    pub async fn set_activity_tags(
+        self,
+        activity_id: i32,
+        mut tag_ids: Vec<i32>,
+        account_id: AccountID,
+    ) -> Result<Option<Vec<Tag>>, Error> {
+        tag_ids.sort();
+        tag_ids.dedup();
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQueryError)?;
+        if let Err(e) = sqlx::query(r#"DELETE FROM activity_tags WHERE activity_id = $1"#)
+            .bind(activity_id)
+            .execute(&mut *tx)
+            .await
+        {
+            error!("Can't clear activity tags with {:?}", e);
+            return Err(Error::DatabaseQueryError(e));
+        }
+        match sqlx::query(
+            r#"INSERT INTO activity_tags (activity_id, tag_id)
+            SELECT $1, id FROM tags WHERE id = ANY($2) and account_id = $3"#,
+        )
+        .bind(activity_id)
+        .bind(&tag_ids)
+        .bind(account_id.0)
+        .execute(&mut *tx)
+        .await
+        {
+            Ok(res) if res.rows_affected() == tag_ids.len() as u64 => {}
+            Ok(_) => return Ok(None),
+            Err(e) => {
+                error!("Can't set activity tags with {:?}", e);
+                return Err(Error::DatabaseQueryError(e));
+            }
+        }
+        tx.commit().await.map_err(Error::DatabaseQueryError)?;
+        Ok(Some(self.get_activity_tags(activity_id, account_id).await?))
+    }
+
+    pub async fn get_projects(self, account_id: AccountID) -> Result<Vec<Project>, Error> {
+        match sqlx::query(&format!(
+            "SELECT p.*, rollup.* FROM projects p, {} WHERE p.account_id = $1 ORDER BY p.id",
+            PROJECT_ROLLUP
+        ))
+        .bind(account_id.0)
+        .map(|row: PgRow| project_from_row(&row))
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(projects) => Ok(projects),
+            Err(e) => {
+                error!("Can't get projects with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn get_project(
+        self,
+        id: i32,
+        account_id: AccountID,
+    ) -> Result<Option<Project>, Error> {
+        match sqlx::query(&format!(
+            "SELECT p.*, rollup.* FROM projects p, {} WHERE p.id = $1 and p.account_id = $2",
+            PROJECT_ROLLUP
+        ))
+        .bind(id)
+        .bind(account_id.0)
+        .map(|row: PgRow| project_from_row(&row))
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(project) => Ok(project),
+            Err(e) => {
+                error!("Can't get project with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Adds a project, or updates the project with `id` when it's owned by the account
+    pub async fn save_project(
+        self,
+        project: NewProject,
+        id: Option<i32>,
+        account_id: AccountID,
+    ) -> Result<Option<Project>, Error> {
+        let saved = match id {
+            Some(_) => {
+                r#"UPDATE projects SET title = $1, content = $2
+                WHERE id = $3 and account_id = $4
+                RETURNING *"#
+            }
+            None => {
+                r#"INSERT INTO projects (title, content, account_id)
+                VALUES ($1, $2, $4)
+                RETURNING *"#
+            }
+        };
+        match sqlx::query(&format!(
+            "WITH p AS ({}) SELECT p.*, rollup.* FROM p, {}",
+            saved, PROJECT_ROLLUP
+        ))
+        .bind(project.title)
+        .bind(project.content)
+        .bind(id)
+        .bind(account_id.0)
+        .map(|row: PgRow| project_from_row(&row))
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(project) => Ok(project),
+            Err(e) => {
+                error!("Can't save project with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Activities of the project stay, they just lose the project
+    pub async fn delete_project(self, id: i32, account_id: AccountID) -> Result<bool, Error> {
+        match sqlx::query(r#"DELETE FROM projects WHERE id = $1 and account_id = $2"#)
+            .bind(id)
+            .bind(account_id.0)
+            .execute(&self.connection)
+            .await
+        {
+            Ok(res) => Ok(res.rows_affected() > 0),
+            Err(e) => {
+                error!("Can't delete project with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn get_project_activities(
+        self,
+        id: i32,
+        account_id: AccountID,
+    ) -> Result<Vec<Activity>, Error> {
+        match sqlx::query(
+            r#"SELECT * from activities
+            where project_id = $1 and account_id = $2 and deleted_at IS NULL
+            ORDER BY id"#,
+        )
+        .bind(id)
+        .bind(account_id.0)
+        .map(activity_from_row)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(activities) => Ok(activities),
+            Err(e) => {
+                error!("Can't get project activities with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Children of the activity, their children and so on, in no particular order
+    pub async fn get_descendants(
+        self,
+        activity_id: i32,
+        account_id: AccountID,
+    ) -> Result<Vec<Activity>, Error> {
+        match sqlx::query(
+            r#"WITH RECURSIVE tree AS (
+                SELECT id from activities where parent_id = $1 and account_id = $2 and deleted_at IS NULL
+                UNION
+                SELECT a.id from activities a JOIN tree ON a.parent_id = tree.id
+                where a.deleted_at IS NULL
+            )
+            SELECT a.* from activities a JOIN tree ON a.id = tree.id"#,
+        )
+        .bind(activity_id)
+        .bind(account_id.0)
+        .map(activity_from_row)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(activities) => Ok(activities),
+            Err(e) => {
+                error!("Can't get descendants with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Whether `activity_id` is `parent_id` or one of its ancestors
+    pub async fn is_ancestor(&self, activity_id: i32, parent_id: i32) -> Result<bool, Error> {
+        match sqlx::query(
+            r#"WITH RECURSIVE ancestors AS (
+                SELECT id, parent_id from activities where id = $1
+                UNION
+                SELECT a.id, a.parent_id from activities a JOIN ancestors ON a.id = ancestors.parent_id
+            )
+            SELECT EXISTS (SELECT 1 from ancestors where id = $2)"#,
+        )
+        .bind(parent_id)
+        .bind(activity_id)
+        .map(|row: PgRow| row.get::<bool, _>(0))
+        .fetch_one(&self.connection)
+        .await
+        {
+            Ok(res) => Ok(res),
+            Err(e) => {
+                error!("Can't check ancestors with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Moves the activity from `from` to `to` and records the change,
+    /// `None` when the activity isn't in `from` status anymore
+    pub async fn set_status(
+        self,
+        activity_id: i32,
+        from: ActivityStatus,
+        to: ActivityStatus,
+        account_id: AccountID,
+    ) -> Result<Option<Activity>, Error> {
+        match sqlx::query(
+            r#"WITH changed AS (
+                UPDATE activities
+                SET status = $3, status_changed_on = NOW(), updated_on = NOW()
+                WHERE id = $1 and account_id = $2 and status = $4 and deleted_at IS NULL
+                RETURNING *
+            ), history AS (
+                INSERT INTO activity_status_history (activity_id, from_status, to_status)
+                SELECT id, $4, status FROM changed
+            )
+            SELECT * FROM changed"#,
+        )
+        .bind(activity_id)
+        .bind(account_id.0)
+        .bind(to.as_str())
+        .bind(from.as_str())
+        .map(activity_from_row)
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(activity) => Ok(activity),
+            Err(e) => {
+                error!("Can't set status with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn get_status_history(
+        self,
+        activity_id: i32,
+        account_id: AccountID,
+    ) -> Result<Vec<StatusChange>, Error> {
+        match sqlx::query(
+            r#"SELECT h.* from activity_status_history h
+            JOIN activities a ON a.id = h.activity_id
+            WHERE h.activity_id = $1 and a.account_id = $2
+            ORDER BY h.changed_on, h.id"#,
+        )
+        .bind(activity_id)
+        .bind(account_id.0)
+        .map(|row: PgRow| {
+            let from: Option<String> = row.get("from_status");
+            let to: String = row.get("to_status");
+            StatusChange {
+                from: from.and_then(|from| from.parse().ok()),
+                to: to.parse().unwrap_or_default(),
+                changed_on: row.get("changed_on"),
+            }
+        })
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(history) => Ok(history),
+            Err(e) => {
+                error!("Can't get status history with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    /// Gives the activity a rank right after the `after` activity, or before all of them.
+    /// `None` when `after` isn't owned by the account.
+    pub async fn move_activity(
+        self,
+        activity_id: i32,
+        after: Option<i32>,
+        account_id: AccountID,
+    ) -> Result<Option<Activity>, Error> {
+        let mut tx = self
+            .connection
+            .begin()
+            .await
+            .map_err(Error::DatabaseQueryError)?;
+
+        lock_ranks(&mut tx, &account_id).await?;
+        let before = match after {
+            Some(after) => {
+                match sqlx::query(
+                    r#"SELECT rank from activities
+                    where id = $1 and account_id = $2 and deleted_at IS NULL"#,
+                )
+                .bind(after)
+                .bind(account_id.0)
+                .map(|row: PgRow| row.get::<String, _>("rank"))
+                .fetch_optional(&mut *tx)
+                .await
+                {
+                    Ok(Some(rank)) => rank,
+                    Ok(None) => return Ok(None),
+                    Err(e) => {
+                        error!("Can't get rank with {:?}", e);
+                        return Err(Error::DatabaseQueryError(e));
+                    }
+                }
+            }
+            None => String::new(),
+        };
+        let next = match sqlx::query(
+            r#"SELECT MIN(rank) AS rank from activities
+            where account_id = $1 and deleted_at IS NULL and rank > $2 and id != $3"#,
+        )
+        .bind(account_id.0)
+        .bind(&before)
+        .bind(activity_id)
+        .map(|row: PgRow| row.get::<Option<String>, _>("rank"))
+        .fetch_one(&mut *tx)
+        .await
+        {
+            Ok(next) => next,
+            Err(e) => {
+                error!("Can't get next rank with {:?}", e);
+                return Err(Error::DatabaseQueryError(e));
+            }
+        };
+        let activity = match sqlx::query(
+            r#"UPDATE activities SET rank = $1, updated_on = NOW()
+            WHERE id = $2 and account_id = $3 and deleted_at IS NULL
+            RETURNING *"#,
+        )
+        .bind(match next {
+            Some(next) => rank_between(&before, Some(&next)),
+            None => rank_after(&before),
+        })
+        .bind(activity_id)
+        .bind(account_id.0)
+        .map(activity_from_row)
+        .fetch_optional(&mut *tx)
+        .await
+        {
+            Ok(activity) => activity,
+            Err(e) => {
+                error!("Can't move activity with {:?}", e);
+                return Err(Error::DatabaseQueryError(e));
+            }
+        };
+
+        tx.commit().await.map_err(Error::DatabaseQueryError)?;
+        Ok(activity)
+    }
+
+    /// Changes of the activity, the oldest first
+    pub async fn get_revisions(
+        self,
+        activity_id: i32,
+        account_id: AccountID,
+    ) -> Result<Vec<Revision>, Error> {
+        match sqlx::query(&format!(
+            r#"SELECT {REVISION_COLUMNS} FROM activity_revisions r
+            JOIN activities a ON a.id = r.activity_id
+            WHERE r.activity_id = $1 and a.account_id = $2
+            ORDER BY r.rev"#
+        ))
+        .bind(activity_id)
+        .bind(account_id.0)
+        .map(revision_from_row)
+        .fetch_all(&self.connection)
+        .await
+        {
+            Ok(revisions) => Ok(revisions),
+            Err(e) => {
+                error!("Can't get revisions with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+
+    pub async fn get_revision(
+        &self,
+        activity_id: i32,
+        rev: i32,
+        account_id: AccountID,
+    ) -> Result<Option<Revision>, Error> {
+        match sqlx::query(&format!(
+            r#"SELECT {REVISION_COLUMNS} FROM activity_revisions r
+            JOIN activities a ON a.id = r.activity_id
+            WHERE r.activity_id = $1 and r.rev = $2 and a.account_id = $3"#
+        ))
+        .bind(activity_id)
+        .bind(rev)
+        .bind(account_id.0)
+        .map(revision_from_row)
+        .fetch_optional(&self.connection)
+        .await
+        {
+            Ok(revision) => Ok(revision),
+            Err(e) => {
+                error!("Can't get revision with {:?}", e);
+                Err(Error::DatabaseQueryError(e))
+            }
+        }
+    }
+}
+
+/// The activity locked until the end of the transaction, `None` when it's gone,
+/// or trashed unless `with_trash`
+async fn lock_activity(
+    connection: &mut PgConnection,
+    activity_id: i32,
+    account_id: &AccountID,
+    with_trash: bool,
+) -> Result<Option<Activity>, Error> {
+    match sqlx::query(
+        r#"SELECT * FROM activities
+        WHERE id = $1 and account_id = $2 and (deleted_at IS NULL or $3)
+        FOR UPDATE"#,
+    )
+    .bind(activity_id)
+    .bind(account_id.0)
+    .bind(with_trash)
+    .map(activity_from_row)
+    .fetch_optional(connection)
+    .await
+    {
+        Ok(activity) => Ok(activity),
+        Err(e) => {
+            error!("Can't lock activity with {:?}", e);
+            Err(Error::DatabaseQueryError(e))
+        }
+    }
+}
+
+/// Records the change in the history, edits which change nothing are skipped
+async fn add_revision(
+    connection: &mut PgConnection,
+    account_id: &AccountID,
+    kind: RevisionKind,
+    reverts: Option<i32>,
+    before: &Activity,
+    after: &Activity,
+) -> Result<(), Error> {
+    let activity_id = before.id.0;
+    let before = ActivitySnapshot::from(before);
+    let after = ActivitySnapshot::from(after);
+    if kind != RevisionKind::Delete && before == after {
+        return Ok(());
+    }
+
+    // The activity row is locked, so revision numbers can't race
+    match sqlx::query(
+        r#"INSERT INTO activity_revisions (activity_id, rev, kind, changed_by, reverts, before, after)
+        SELECT $1, COALESCE(MAX(rev), 0) + 1, $2, $3, $4, $5::jsonb, $6::jsonb
+        FROM activity_revisions WHERE activity_id = $1"#,
+    )
+    .bind(activity_id)
+    .bind(kind.as_str())
+    .bind(account_id.0)
+    .bind(reverts)
+    .bind(serde_json::to_string(&before).unwrap_or_default())
+    .bind(serde_json::to_string(&after).unwrap_or_default())
+    .execute(connection)
+    .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Can't add revision with {:?}", e);
+            Err(Error::DatabaseQueryError(e))
+        }
+    }
+}
+
+/// Single accounting path for booked time, keeps `spent` of the activity in sync with entries.
+/// The change is recorded in the history of the locked `before` activity as `kind`.
+async fn add_spent(
+    connection: &mut PgConnection,
+    before: &Activity,
+    account_id: &AccountID,
+    kind: RevisionKind,
+    duration: i32,
+) -> Result<(), Error> {
+    let after = match sqlx::query(
+        r#"UPDATE activities SET spent = spent + $1
+        WHERE id = $2 and account_id = $3
+        RETURNING *"#,
+    )
+    .bind(duration)
+    .bind(before.id.0)
+    .bind(account_id.0)
+    .map(activity_from_row)
+    .fetch_optional(&mut *connection)
+    .await
+    {
+        Ok(Some(after)) => after,
+        Ok(None) => {
+            error!("Can't book time for not existing activity {:?}", before.id);
+            return Err(Error::DatabaseQueryError(sqlx::Error::RowNotFound));
+        }
+        Err(e) => {
+            error!("Can't book time with {:?}", e);
+            return Err(Error::DatabaseQueryError(e));
+        }
+    };
+    add_revision(connection, account_id, kind, None, before, &after).await
+}
+
+/// Serializes rank changes of the account until the transaction ends
+async fn lock_ranks(connection: &mut PgConnection, account_id: &AccountID) -> Result<(), Error> {
+    match sqlx::query(r#"SELECT pg_advisory_xact_lock($1::bigint)"#)
+        .bind(account_id.0)
+        .execute(connection)
+        .await
+    {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            error!("Can't lock ranks with {:?}", e);
+            Err(Error::DatabaseQueryError(e))
+        }
+    }
+}
+
+/// The biggest rank of the account activities, empty when there are none
+async fn last_rank(connection: &mut PgConnection, account_id: &AccountID) -> Result<String, Error> {
+    match sqlx::query(r#"SELECT MAX(rank) AS rank from activities where account_id = $1"#)
+        .bind(account_id.0)
+        .map(|row: PgRow| row.get::<Option<String>, _>("rank"))
+        .fetch_one(connection)
+        .await
+    {
+        Ok(rank) => Ok(rank.unwrap_or_default()),
+        Err(e) => {
+            error!("Can't get last rank with {:?}", e);
+            Err(Error::DatabaseQueryError(e))
+        }
+    }
+}
+
+/// Checks entries of the activity crossing the range, `except` entry is skipped
+async fn has_overlapping_entry(
+    connection: &mut PgConnection,
+    entry: &NewTimeEntry,
+    account_id: &AccountID,
+    except: Option<i32>,
+) -> Result<bool, Error> {
+    match sqlx::query(
+        r#"SELECT id from time_entries
+        WHERE activity_id = $1 and account_id = $2
+        and started_at < $4 and stopped_at > $3
+        and ($5::integer IS NULL or id <> $5)
+        LIMIT 1"#,
+    )
+    .bind(entry.activity_id.0)
+    .bind(account_id.0)
+    .bind(entry.started_at)
+    .bind(entry.stopped_at)
+    .bind(except)
+    .fetch_optional(connection)
+    .await
+    {
+        Ok(entry) => Ok(entry.is_some()),
+        Err(e) => {
+            error!("Can't check overlapping time entries with {:?}", e);
+            Err(Error::DatabaseQueryError(e))
+        }
+    }
+}
+
+/// Conditions of the activity list, shared by the list and its count
+fn push_activity_filter(
+    query: &mut QueryBuilder<'_, Postgres>,
+    account_id: &AccountID,
+    filter: &ActivityFilter,
+) {
+    query
+        .push("account_id = ")
+        .push_bind(account_id.0)
+        .push(" AND deleted_at IS NULL");
+    if let Some(tag) = &filter.tag {
+        query
+            .push(
+                " AND id IN (SELECT activity_tags.activity_id FROM activity_tags \
+                JOIN tags ON tags.id = activity_tags.tag_id WHERE tags.account_id = ",
+            )
+            .push_bind(account_id.0)
+            .push(" AND tags.name = ")
+            .push_bind(tag.clone())
+            .push(")");
+    }
+    if let Some(status) = filter.status {
+        query.push(" AND status = ").push_bind(status.as_str());
+    }
+    if let Some(project_id) = filter.project_id {
+        query.push(" AND project_id = ").push_bind(project_id);
+    }
+    if let Some(due_from) = filter.due_from {
+        query.push(" AND due_at >= ").push_bind(due_from);
+    }
+    if let Some(due_to) = filter.due_to {
+        query.push(" AND due_at < ").push_bind(due_to);
+    }
+    match filter.has_remaining {
+        Some(true) => {
+            query.push(" AND spent < time");
+        }
+        Some(false) => {
+            query.push(" AND spent >= time");
+        }
+        None => {}
+    }
+    if let Some(pattern) = filter.search_pattern() {
+        query
+            .push(" AND (title ILIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR content ILIKE ")
+            .push_bind(pattern)
+            .push(")");
+    }
+}
+
+/// Rows strictly after the cursor values in the order of the keys
+fn push_keyset(
+    query: &mut QueryBuilder<'_, Postgres>,
+    keys: &[SortKey],
+    values: &[Option<String>],
+) {
+    let (Some(key), Some(value)) = (keys.first(), values.first()) else {
+        query.push("FALSE");
+        return;
+    };
+    query.push("(");
+    match value {
+        Some(value) => {
+            query
+                .push(key.column)
+                .push(if key.desc { " < " } else { " > " })
+                .push_bind(value.clone())
+                .push("::")
+                .push(key.sql_type);
+            if key.nulls_last == Some(true) {
+                query.push(" OR ").push(key.column).push(" IS NULL");
+            }
+        }
+        None if key.nulls_last == Some(false) => {
+            query.push(key.column).push(" IS NOT NULL");
+        }
+        None => {
+            query.push("FALSE");
+        }
+    }
+    if keys.len() > 1 {
+        query.push(" OR (");
+        match value {
+            Some(value) => {
+                query
+                    .push(key.column)
+                    .push(" = ")
+                    .push_bind(value.clone())
+                    .push("::")
+                    .push(key.sql_type);
+            }
+            None => {
+                query.push(key.column).push(" IS NULL");
+            }
+        }
+        query.push(" AND ");
+        push_keyset(query, &keys[1..], &values[1..]);
+        query.push(")");
+    }
+    query.push(")");
+}
+
+fn activity_from_row(row: PgRow) -> Activity {
+    let time: i32 = row.get("time");
+    let spent: i32 = row.get("spent");
+    let status: String = row.get("status");
+    Activity {
+        id: ActivityId(row.get("id")),
+        title: row.get("title"),
+        content: row.get("content"),
+        time,
+        spent,
+        remaining: (time - spent).max(0),
+        overtime: (spent - time).max(0),
+        is_overdue: spent > time,
+        scheduled_start: row.get("scheduled_start"),
+        scheduled_end: row.get("scheduled_end"),
+        due_at: row.get("due_at"),
+        project_id: row.get::<Option<i32>, _>("project_id").map(ProjectId),
+        parent_id: row.get::<Option<i32>, _>("parent_id").map(ActivityId),
+        status: status.parse().unwrap_or_default(),
+        status_changed_on: row.get("status_changed_on"),
+        priority: row.get("priority"),
+        rank: row.get("rank"),
+        created_on: row.get("created_on"),
+        updated_on: row.get("updated_on"),
+    }
+}
+
+fn time_entry_from_row(row: PgRow) -> TimeEntry {
+    TimeEntry {
+        id: TimeEntryId(row.get("id")),
+        activity_id: ActivityId(row.get("activity_id")),
+        started_at: row.get("started_at"),
+        stopped_at: row.get("stopped_at"),
+        duration: row.get("duration"),
+        needs_review: row.get("needs_review"),
+    }
+}
+
+fn recurrence_from_row(row: &PgRow) -> Recurrence {
+    let frequency: String = row.get("frequency");
+    let by_weekday: Vec<i16> = row.get("by_weekday");
+    Recurrence {
+        activity_id: ActivityId(row.get("activity_id")),
+        frequency: frequency.parse().unwrap_or(Frequency::Daily),
+        interval: row.get("repeat_interval"),
+        by_weekday: by_weekday
+            .into_iter()
+            .filter_map(|day| Weekday::try_from(day as u8).ok())
+            .map(Day::from)
+            .collect(),
+        starts_at: row.get("starts_at"),
+        until: row.get("until"),
+        count: row.get("count"),
+        exceptions: row.get("exceptions"),
+    }
+}
+
+fn availability_rule_from_row(row: &PgRow) -> AvailabilityRule {
+    let weekday: i16 = row.get("weekday");
+    let kind: String = row.get("kind");
+    AvailabilityRule {
+        id: row.get("id"),
+        weekday: Weekday::try_from(weekday as u8)
+            .unwrap_or(Weekday::Mon)
+            .into(),
+        kind: match kind.as_str() {
+            "break" => RuleKind::Break,
+            _ => RuleKind::Work,
+        },
+        start_time: row.get("start_time"),
+        end_time: row.get("end_time"),
+    }
+}
+
+fn time_off_from_row(row: &PgRow) -> TimeOff {
+    TimeOff {
+        id: row.get("id"),
+        starts_at: row.get("starts_at"),
+        ends_at: row.get("ends_at"),
+        reason: row.get("reason"),
+    }
+}
+
+fn preferences_from_row(row: &PgRow) -> Preferences {
+    let week_start: i16 = row.get("week_start");
+    let time_format: String = row.get("time_format");
+    Preferences {
+        single_active_timer: row.get("single_active_timer"),
+        timezone: row.get("timezone"),
+        week_start: Weekday::try_from(week_start as u8)
+            .unwrap_or(Weekday::Mon)
+            .into(),
+        default_activity_duration: row.get("default_activity_duration"),
+        time_format: match time_format.as_str() {
+            "12h" => TimeFormat::H12,
+            _ => TimeFormat::H24,
+        },
+    }
+}
+
+fn tag_from_row(row: &PgRow) -> Tag {
+    Tag {
+        id: row.get("id"),
+        name: row.get("name"),
+        color: row.get("color"),
+    }
+}
+
+fn project_from_row(row: &PgRow) -> Project {
+    Project {
+        id: ProjectId(row.get("id")),
+        title: row.get("title"),
+        content: row.get("content"),
+        created_on: row.get("created_on"),
+        rollup: Rollup {
+            time: row.get("total_time"),
+            spent: row.get("total_spent"),
+            remaining: row.get("total_remaining"),
+            overtime: row.get("total_overtime"),
+        },
+    }
+}
+
+fn revision_from_row(row: PgRow) -> Revision {
+    let kind: String = row.get("kind");
+    let before: ActivitySnapshot = serde_json::from_str(row.get("before")).unwrap_or_default();
+    let after: ActivitySnapshot = serde_json::from_str(row.get("after")).unwrap_or_default();
+    Revision {
+        rev: row.get("rev"),
+        kind: kind.parse().unwrap_or_default(),
+        changed_by: AccountID(row.get("changed_by")),
+        changed_on: row.get("changed_on"),
+        reverts: row.get("reverts"),
+        changes: before.changes(&after),
+        before,
+        after,
+    }
 }
@@ -26,12 +26,58 @@ use crate::VERSION;
     routes::authentication::register,
     routes::authentication::login,
     routes::activities::get_activities,
+    routes::activities::search_activities,
     routes::activities::get_activity_by_id,
     routes::activities::add_activity,
     routes::activities::update_activities,
     routes::activities::deleted_activities,
+    routes::activities::move_activity,
+    routes::activities::set_status,
+    routes::activities::get_status_history,
+    routes::activities::get_history,
+    routes::activities::revert_activity,
+    routes::entries::get_entries,
+    routes::entries::add_entry,
+    routes::entries::update_entry,
+    routes::entries::delete_entry,
+    routes::recurrence::get_recurrence,
+    routes::recurrence::set_recurrence,
+    routes::recurrence::delete_recurrence,
+    routes::recurrence::get_occurrences,
+    routes::plan::plan_day,
+    routes::plan::accept_plan,
+    routes::freebusy::get_freebusy,
+    routes::preferences::get_preferences,
+    routes::preferences::update_preferences,
+    routes::availability::get_availability,
+    routes::availability::add_rule,
+    routes::availability::update_rule,
+    routes::availability::delete_rule,
+    routes::availability::add_time_off,
+    routes::availability::update_time_off,
+    routes::availability::delete_time_off,
+    routes::tags::get_tags,
+    routes::tags::add_tag,
+    routes::tags::update_tag,
+    routes::tags::delete_tag,
+    routes::tags::get_activity_tags,
+    routes::tags::set_activity_tags,
+    routes::projects::get_projects,
+    routes::projects::get_project,
+    routes::projects::add_project,
+    routes::projects::update_project,
+    routes::projects::delete_project,
+    routes::projects::get_project_activities,
+    routes::projects::get_activity_tree,
+    routes::trash::get_trash,
+    routes::trash::restore_activity,
+    routes::timer::list,
+    routes::timer::status,
     routes::timer::start,
+    routes::timer::pause,
+    routes::timer::resume,
     routes::timer::stop,
+    routes::events::events,
 ))]
 pub struct SchedulerApi;
 
@@ -14,9 +14,38 @@ use chrono::Utc;
 use crate::{
     cache::CacheStore,
     store::Store,
-    types::{account::Account, activities::NewActivity},
+    types::{
+        account::Account,
+        activities::{Activity, ActivityId, NewActivity},
+    },
 };
 
+/// Activity with `time` budget and `spent` seconds, the rest is left default
+pub fn test_activity(id: i32, time: i32, spent: i32) -> Activity {
+    let now = Utc::now();
+    Activity {
+        id: ActivityId(id),
+        title: "test".to_string(),
+        content: "test".to_string(),
+        time,
+        spent,
+        remaining: (time - spent).max(0),
+        overtime: (spent - time).max(0),
+        is_overdue: spent > time,
+        scheduled_start: None,
+        scheduled_end: None,
+        due_at: None,
+        project_id: None,
+        parent_id: None,
+        status: Default::default(),
+        status_changed_on: now,
+        priority: 0,
+        rank: "i".to_string(),
+        created_on: now,
+        updated_on: now,
+    }
+}
+
 #[allow(dead_code)]
 impl Store {
     pub async fn add_test_account(self, id: i32) -> Option<Account> {
@@ -38,7 +67,13 @@ impl Store {
         let record = NewActivity {
             title: "test".to_string(),
             content: "test".to_string(),
-            time: 1,
+            time: Some(1),
+            scheduled_start: None,
+            scheduled_end: None,
+            due_at: None,
+            project_id: None,
+            parent_id: None,
+            priority: None,
         };
         match self
             .add_activity(record, crate::types::account::AccountID(1))
@@ -58,7 +93,34 @@ impl Store {
                 content TEXT NOT NULL,
                 time integer NOT NULL,
                 account_id serial NOT NULL,
-                created_on TIMESTAMP NOT NULL DEFAULT NOW()
+                created_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                spent integer NOT NULL DEFAULT 0,
+                updated_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                scheduled_start TIMESTAMPTZ,
+                scheduled_end TIMESTAMPTZ,
+                due_at TIMESTAMPTZ,
+                project_id integer REFERENCES projects (id) ON DELETE SET NULL,
+                parent_id integer REFERENCES activities (id) ON DELETE SET NULL,
+                status VARCHAR (16) NOT NULL DEFAULT 'todo',
+                status_changed_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                priority smallint NOT NULL DEFAULT 0,
+                rank TEXT COLLATE \"C\" NOT NULL DEFAULT 'i',
+                deleted_at TIMESTAMPTZ,
+                search tsvector GENERATED ALWAYS AS (
+                    setweight(to_tsvector('simple', title), 'A') ||
+                    setweight(to_tsvector('simple', content), 'B')
+                ) STORED
+            );"
+            .to_string(),
+        );
+        tables.insert(
+            "projects".to_string(),
+            "CREATE TABLE IF NOT EXISTS projects (
+                id serial PRIMARY KEY,
+                account_id integer NOT NULL,
+                title VARCHAR (255) NOT NULL,
+                content TEXT NOT NULL DEFAULT '',
+                created_on TIMESTAMPTZ NOT NULL DEFAULT NOW()
             );"
             .to_string(),
         );
@@ -71,6 +133,116 @@ impl Store {
                 );"
             .to_string(),
         );
+        tables.insert(
+            "time_entries".to_string(),
+            "CREATE TABLE IF NOT EXISTS time_entries (
+                id serial PRIMARY KEY,
+                activity_id integer NOT NULL REFERENCES activities (id) ON DELETE CASCADE,
+                account_id integer NOT NULL,
+                started_at TIMESTAMPTZ NOT NULL,
+                stopped_at TIMESTAMPTZ NOT NULL,
+                duration integer NOT NULL,
+                needs_review BOOLEAN NOT NULL DEFAULT FALSE
+            );"
+            .to_string(),
+        );
+        tables.insert(
+            "account_preferences".to_string(),
+            "CREATE TABLE IF NOT EXISTS account_preferences (
+                account_id integer PRIMARY KEY,
+                single_active_timer BOOLEAN NOT NULL DEFAULT FALSE,
+                timezone VARCHAR (64) NOT NULL DEFAULT 'UTC',
+                week_start smallint NOT NULL DEFAULT 0,
+                default_activity_duration integer NOT NULL DEFAULT 30,
+                time_format VARCHAR (8) NOT NULL DEFAULT '24h'
+            );"
+            .to_string(),
+        );
+        tables.insert(
+            "activity_recurrences".to_string(),
+            "CREATE TABLE IF NOT EXISTS activity_recurrences (
+                activity_id integer PRIMARY KEY REFERENCES activities (id) ON DELETE CASCADE,
+                account_id integer NOT NULL,
+                frequency VARCHAR (16) NOT NULL,
+                repeat_interval integer NOT NULL DEFAULT 1,
+                by_weekday smallint[] NOT NULL DEFAULT '{}',
+                starts_at TIMESTAMPTZ NOT NULL,
+                until TIMESTAMPTZ,
+                count integer,
+                exceptions DATE[] NOT NULL DEFAULT '{}'
+            );"
+            .to_string(),
+        );
+        tables.insert(
+            "availability_rules".to_string(),
+            "CREATE TABLE IF NOT EXISTS availability_rules (
+                id serial PRIMARY KEY,
+                account_id integer NOT NULL,
+                weekday smallint NOT NULL,
+                kind VARCHAR (16) NOT NULL,
+                start_time TIME NOT NULL,
+                end_time TIME NOT NULL
+            );"
+            .to_string(),
+        );
+        tables.insert(
+            "time_off".to_string(),
+            "CREATE TABLE IF NOT EXISTS time_off (
+                id serial PRIMARY KEY,
+                account_id integer NOT NULL,
+                starts_at TIMESTAMPTZ NOT NULL,
+                ends_at TIMESTAMPTZ NOT NULL,
+                reason TEXT
+            );"
+            .to_string(),
+        );
+        tables.insert(
+            "activity_status_history".to_string(),
+            "CREATE TABLE IF NOT EXISTS activity_status_history (
+                id serial PRIMARY KEY,
+                activity_id integer NOT NULL REFERENCES activities (id) ON DELETE CASCADE,
+                from_status VARCHAR (16),
+                to_status VARCHAR (16) NOT NULL,
+                changed_on TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            );"
+            .to_string(),
+        );
+        tables.insert(
+            "activity_revisions".to_string(),
+            "CREATE TABLE IF NOT EXISTS activity_revisions (
+                id serial PRIMARY KEY,
+                activity_id integer NOT NULL REFERENCES activities (id) ON DELETE CASCADE,
+                rev integer NOT NULL,
+                kind VARCHAR (16) NOT NULL,
+                changed_by integer NOT NULL,
+                changed_on TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                reverts integer,
+                before JSONB NOT NULL,
+                after JSONB NOT NULL,
+                UNIQUE (activity_id, rev)
+            );"
+            .to_string(),
+        );
+        tables.insert(
+            "tags".to_string(),
+            "CREATE TABLE IF NOT EXISTS tags (
+                id serial PRIMARY KEY,
+                account_id integer NOT NULL,
+                name VARCHAR (64) NOT NULL,
+                color VARCHAR (16),
+                UNIQUE (account_id, name)
+            );"
+            .to_string(),
+        );
+        tables.insert(
+            "activity_tags".to_string(),
+            "CREATE TABLE IF NOT EXISTS activity_tags (
+                activity_id integer NOT NULL REFERENCES activities (id) ON DELETE CASCADE,
+                tag_id integer NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+                PRIMARY KEY (activity_id, tag_id)
+            );"
+            .to_string(),
+        );
         match tables.get(name) {
             Some(insert) => sqlx::query(insert)
                 .fetch_all(&self.connection)
@@ -98,7 +270,17 @@ pub async fn prepare_store(port: u16) -> Result<Store, sqlx::Error> {
     .unwrap();
 
     store.add_tables("accounts").await;
+    store.add_tables("projects").await;
     store.add_tables("activities").await;
+    store.add_tables("time_entries").await;
+    store.add_tables("account_preferences").await;
+    store.add_tables("activity_recurrences").await;
+    store.add_tables("availability_rules").await;
+    store.add_tables("time_off").await;
+    store.add_tables("activity_status_history").await;
+    store.add_tables("activity_revisions").await;
+    store.add_tables("tags").await;
+    store.add_tables("activity_tags").await;
     Ok(store)
 }
 
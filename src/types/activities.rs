@@ -1,27 +1,567 @@
-use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
+use std::str::FromStr;
+
+use chrono::prelude::*;
+use serde::{Deserialize, Deserializer, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::types::projects::ProjectId;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, Hash, PartialEq, ToSchema)]
 pub struct ActivityId(pub i32);
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityStatus {
+    #[default]
+    Todo,
+    InProgress,
+    Done,
+    Cancelled,
+    Archived,
+}
+
+impl ActivityStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityStatus::Todo => "todo",
+            ActivityStatus::InProgress => "in_progress",
+            ActivityStatus::Done => "done",
+            ActivityStatus::Cancelled => "cancelled",
+            ActivityStatus::Archived => "archived",
+        }
+    }
+
+    /// Done and cancelled activities can be reopened, archived ones only go back to todo
+    pub fn can_become(&self, to: ActivityStatus) -> bool {
+        use ActivityStatus::*;
+        matches!(
+            (self, to),
+            (Todo, InProgress | Done | Cancelled)
+                | (InProgress, Todo | Done | Cancelled)
+                | (Done, InProgress | Archived)
+                | (Cancelled, Todo | Archived)
+                | (Archived, Todo)
+        )
+    }
+
+    pub fn transition(&self, to: ActivityStatus) -> Result<ActivityStatus, handle_errors::Error> {
+        match self.can_become(to) {
+            true => Ok(to),
+            false => Err(handle_errors::Error::InvalidStatusTransition),
+        }
+    }
+}
+
+impl FromStr for ActivityStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "todo" => Ok(ActivityStatus::Todo),
+            "in_progress" => Ok(ActivityStatus::InProgress),
+            "done" => Ok(ActivityStatus::Done),
+            "cancelled" => Ok(ActivityStatus::Cancelled),
+            "archived" => Ok(ActivityStatus::Archived),
+            _ => Err(format!("unknown status {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct NewStatus {
+    pub status: ActivityStatus,
+}
+
+/// One entry of the status history, `from` is empty for the initial status
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct StatusChange {
+    pub from: Option<ActivityStatus>,
+    pub to: ActivityStatus,
+    pub changed_on: DateTime<Utc>,
+}
+
+/// `time` is the estimate, the rest is calculated from the booked time, all in seconds
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct Activity {
     pub id: ActivityId,
     pub title: String,
     pub content: String,
     pub time: i32,
+    pub spent: i32,
+    pub remaining: i32,
+    pub overtime: i32,
+    pub is_overdue: bool,
+    pub scheduled_start: Option<DateTime<Utc>>,
+    pub scheduled_end: Option<DateTime<Utc>>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub project_id: Option<ProjectId>,
+    pub parent_id: Option<ActivityId>,
+    pub status: ActivityStatus,
+    /// From 0 (none) to 4 (urgent)
+    pub priority: i16,
+    /// Position in the manual order, compared as bytes
+    pub rank: String,
+    pub status_changed_on: DateTime<Utc>,
+    pub created_on: DateTime<Utc>,
+    pub updated_on: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct NewActivity {
     pub title: String,
     pub content: String,
-    pub time: i32,
+    /// Falls back to the account default activity duration
+    pub time: Option<i32>,
+    pub scheduled_start: Option<DateTime<Utc>>,
+    pub scheduled_end: Option<DateTime<Utc>>,
+    pub due_at: Option<DateTime<Utc>>,
+    /// Children without a project join the project of the parent
+    pub project_id: Option<ProjectId>,
+    pub parent_id: Option<ActivityId>,
+    pub priority: Option<i16>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivitySort {
+    /// Manual order
+    #[default]
+    Rank,
+    /// The most urgent first, manual order inside of a priority
+    Priority,
+    DueAt,
+    CreatedOn,
+    UpdatedOn,
+    Title,
+    Remaining,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+/// Column of the list order, the last keys make the order total
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SortKey {
+    pub column: &'static str,
+    pub sql_type: &'static str,
+    pub desc: bool,
+    /// Where activities without a value go, `None` when the column can't be empty
+    pub nulls_last: Option<bool>,
+}
+
+impl SortKey {
+    fn new(column: &'static str, sql_type: &'static str, desc: bool) -> Self {
+        SortKey {
+            column,
+            sql_type,
+            desc,
+            nulls_last: None,
+        }
+    }
+
+    /// Same key read backwards, used to walk to the previous page
+    pub fn reversed(self) -> Self {
+        SortKey {
+            desc: !self.desc,
+            nulls_last: self.nulls_last.map(|nulls_last| !nulls_last),
+            ..self
+        }
+    }
+
+    fn order_by(&self) -> String {
+        let direction = if self.desc { " DESC" } else { "" };
+        let nulls = match self.nulls_last {
+            Some(true) => " NULLS LAST",
+            Some(false) => " NULLS FIRST",
+            None => "",
+        };
+        format!("{}{direction}{nulls}", self.column)
+    }
 }
 
+impl ActivitySort {
+    fn column(&self) -> (&'static str, &'static str) {
+        match self {
+            ActivitySort::Rank => ("rank", "text"),
+            ActivitySort::Priority => ("priority", "smallint"),
+            ActivitySort::DueAt => ("due_at", "timestamptz"),
+            ActivitySort::CreatedOn => ("created_on", "timestamptz"),
+            ActivitySort::UpdatedOn => ("updated_on", "timestamptz"),
+            ActivitySort::Title => ("title", "text"),
+            ActivitySort::Remaining => ("GREATEST(time - spent, 0)", "integer"),
+        }
+    }
+
+    pub fn resolve(&self, order: Option<SortOrder>) -> SortOrder {
+        order.unwrap_or(match self {
+            ActivitySort::Priority => SortOrder::Desc,
+            _ => SortOrder::Asc,
+        })
+    }
+
+    /// Ties of the sort field keep the manual order
+    pub fn keys(&self, order: Option<SortOrder>) -> Vec<SortKey> {
+        let desc = self.resolve(order) == SortOrder::Desc;
+        match self {
+            ActivitySort::Rank => vec![
+                SortKey::new("rank", "text", desc),
+                SortKey::new("id", "integer", desc),
+            ],
+            _ => {
+                let (column, sql_type) = self.column();
+                let key = SortKey {
+                    nulls_last: (*self == ActivitySort::DueAt).then_some(true),
+                    ..SortKey::new(column, sql_type, desc)
+                };
+                vec![
+                    key,
+                    SortKey::new("rank", "text", false),
+                    SortKey::new("id", "integer", false),
+                ]
+            }
+        }
+    }
+
+    pub fn order_by(&self, order: Option<SortOrder>) -> String {
+        order_by(&self.keys(order))
+    }
+
+    /// Value of the sort field as the cursor keeps it, `None` for the manual order
+    pub fn value(&self, activity: &Activity) -> Option<String> {
+        match self {
+            ActivitySort::Rank => None,
+            ActivitySort::Priority => Some(activity.priority.to_string()),
+            ActivitySort::DueAt => activity.due_at.map(|due_at| due_at.to_rfc3339()),
+            ActivitySort::CreatedOn => Some(activity.created_on.to_rfc3339()),
+            ActivitySort::UpdatedOn => Some(activity.updated_on.to_rfc3339()),
+            ActivitySort::Title => Some(activity.title.clone()),
+            ActivitySort::Remaining => Some(activity.remaining.to_string()),
+        }
+    }
+}
+
+pub fn order_by(keys: &[SortKey]) -> String {
+    keys.iter()
+        .map(SortKey::order_by)
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// Filters and order of the activity list, all filters are combined
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ActivityFilter {
+    /// Name of a tag the activities should have
+    #[param(inline)]
+    pub tag: Option<String>,
+    #[param(inline)]
+    pub status: Option<ActivityStatus>,
+    #[param(inline)]
+    pub project_id: Option<i32>,
+    /// Deadline at or after this moment
+    #[param(inline)]
+    pub due_from: Option<DateTime<Utc>>,
+    /// Deadline before this moment
+    #[param(inline)]
+    pub due_to: Option<DateTime<Utc>>,
+    /// Only activities with (or without) time left
+    #[param(inline)]
+    pub has_remaining: Option<bool>,
+    /// Part of the title or content, case insensitive
+    #[param(inline)]
+    pub search: Option<String>,
+    #[param(inline)]
+    pub sort: Option<ActivitySort>,
+    #[param(inline)]
+    pub order: Option<SortOrder>,
+}
+
+impl ActivityFilter {
+    pub fn validate(self) -> Result<Self, handle_errors::Error> {
+        if let (Some(from), Some(to)) = (self.due_from, self.due_to) {
+            if to <= from {
+                return Err(handle_errors::Error::InvalidTimeRange);
+            }
+        }
+        let search = self
+            .search
+            .map(|search| search.trim().to_string())
+            .filter(|search| !search.is_empty());
+        let tag = self
+            .tag
+            .map(|tag| tag.trim().to_string())
+            .filter(|tag| !tag.is_empty());
+        Ok(ActivityFilter {
+            search,
+            tag,
+            ..self
+        })
+    }
+
+    /// `ILIKE` pattern of the search with its wildcards escaped
+    pub fn search_pattern(&self) -> Option<String> {
+        self.search.as_ref().map(|search| {
+            let escaped = search
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_");
+            format!("%{escaped}%")
+        })
+    }
+}
+
+/// Puts the activity right after `after`, first when it's empty
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct MoveActivity {
+    pub after: Option<ActivityId>,
+}
+
+/// Missing fields keep the old value, `null` clears the schedule, project and parent
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct PartiaActivity {
     pub title: Option<String>,
     pub content: Option<String>,
     pub time: Option<i32>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<DateTime<Utc>>)]
+    pub scheduled_start: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<DateTime<Utc>>)]
+    pub scheduled_end: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<DateTime<Utc>>)]
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<ProjectId>)]
+    pub project_id: Option<Option<ProjectId>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<ActivityId>)]
+    pub parent_id: Option<Option<ActivityId>>,
+    pub priority: Option<i16>,
+}
+
+/// Tells a present `null` apart from a missing field
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Deserialize::deserialize(deserializer).map(Some)
+}
+
+/// Scheduled end should be after the start when both are set
+pub fn check_schedule(
+    scheduled_start: Option<DateTime<Utc>>,
+    scheduled_end: Option<DateTime<Utc>>,
+) -> Result<(), handle_errors::Error> {
+    match (scheduled_start, scheduled_end) {
+        (Some(start), Some(end)) if end <= start => Err(handle_errors::Error::InvalidTimeRange),
+        _ => Ok(()),
+    }
+}
+
+pub const MAX_PRIORITY: i16 = 4;
+
+pub fn check_priority(priority: Option<i16>) -> Result<(), handle_errors::Error> {
+    match priority {
+        Some(priority) if !(0..=MAX_PRIORITY).contains(&priority) => {
+            Err(handle_errors::Error::InvalidPriority)
+        }
+        _ => Ok(()),
+    }
+}
+
+const RANK_DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+fn rank_digit(c: u8) -> usize {
+    RANK_DIGITS.iter().position(|d| *d == c).unwrap_or(0)
+}
+
+/// Rank sorting between `before` and `after`, an empty `before` is the start
+/// and a missing `after` is the end. Ranks never end with `0`, so there is
+/// always room for another one in between without touching other rows.
+pub fn rank_between(before: &str, after: Option<&str>) -> String {
+    if let Some(after) = after {
+        let prefix = after
+            .bytes()
+            .zip(before.bytes().chain(std::iter::repeat(b'0')))
+            .take_while(|(a, b)| a == b)
+            .count();
+        if prefix > 0 {
+            let rest = before.get(prefix..).unwrap_or("");
+            return format!(
+                "{}{}",
+                &after[..prefix],
+                rank_between(rest, Some(&after[prefix..]))
+            );
+        }
+    }
+    let low = before.bytes().next().map(rank_digit).unwrap_or(0);
+    let high = after
+        .and_then(|after| after.bytes().next())
+        .map(rank_digit)
+        .unwrap_or(RANK_DIGITS.len());
+    if high - low > 1 {
+        return (RANK_DIGITS[(low + high) / 2] as char).to_string();
+    }
+    match after {
+        Some(after) if after.len() > 1 => after[..1].to_string(),
+        _ => format!(
+            "{}{}",
+            RANK_DIGITS[low] as char,
+            rank_between(before.get(1..).unwrap_or(""), None)
+        ),
+    }
+}
+
+/// Rank after `last` for appending, increments the first
+/// `RANK_WIDTH` digits so the length stays the same for every append
+pub fn rank_after(last: &str) -> String {
+    const RANK_WIDTH: usize = 8;
+    if last.is_empty() {
+        return rank_between("", None);
+    }
+    let mut digits: Vec<usize> = last
+        .bytes()
+        .chain(std::iter::repeat(b'0'))
+        .take(RANK_WIDTH)
+        .map(rank_digit)
+        .collect();
+    for i in (0..digits.len()).rev() {
+        if digits[i] + 1 < RANK_DIGITS.len() {
+            digits[i] += 1;
+            let rank: String = digits.iter().map(|d| RANK_DIGITS[*d] as char).collect();
+            return rank.trim_end_matches('0').to_string();
+        }
+        digits[i] = 0;
+    }
+    rank_between(last, None)
+}
+
+#[cfg(test)]
+mod test_activities {
+    use super::*;
+
+    #[test]
+    fn small_test_partial_activity_tells_null_from_missing() {
+        let partial: PartiaActivity =
+            serde_json::from_str(r#"{"title": "test", "due_at": null}"#).unwrap();
+        assert_eq!(partial.due_at, Some(None));
+        assert_eq!(partial.scheduled_start, None);
+    }
+
+    #[test]
+    fn small_test_schedule_should_end_after_start() {
+        let start: DateTime<Utc> = "2026-10-18T09:00:00Z".parse().unwrap();
+        assert!(check_schedule(Some(start), Some(start)).is_err());
+        assert!(check_schedule(Some(start), None).is_ok());
+        assert!(check_schedule(Some(start), Some(start + chrono::TimeDelta::hours(1))).is_ok());
+    }
+
+    #[test]
+    fn small_test_status_transitions() {
+        use ActivityStatus::*;
+        assert_eq!(Todo.transition(InProgress).unwrap(), InProgress);
+        assert!(Done.transition(InProgress).is_ok());
+        assert!(Todo.transition(Archived).is_err());
+        assert!(Archived.transition(Done).is_err());
+        assert!(Cancelled.transition(InProgress).is_err());
+        assert!(Todo.transition(Todo).is_err());
+        assert_eq!("in_progress".parse::<ActivityStatus>().unwrap(), InProgress);
+    }
+
+    #[test]
+    fn small_test_rank_between_keeps_order() {
+        assert_eq!(rank_between("", None), "i");
+        assert_eq!(rank_between("i", None), "r");
+        assert_eq!(rank_between("", Some("i")), "9");
+        assert_eq!(rank_between("a", Some("b")), "ai");
+        assert_eq!(rank_between("a", Some("ai")), "a9");
+        assert_eq!(rank_between("z", None), "zi");
+        assert_eq!(rank_between("", Some("01")), "00i");
+
+        // inserting at the same place again and again keeps the order strict
+        let (mut low, high) = ("a".to_string(), "b".to_string());
+        for _ in 0..100 {
+            let rank = rank_between(&low, Some(&high));
+            assert!(low < rank && rank < high, "{} {} {}", low, rank, high);
+            assert!(!rank.ends_with('0'));
+            low = rank;
+        }
+    }
+
+    #[test]
+    fn small_test_rank_after_keeps_length() {
+        assert_eq!(rank_after(""), "i");
+        assert_eq!(rank_after("i"), "i0000001");
+        assert_eq!(rank_after("i000000z"), "i000001");
+        assert_eq!(rank_after("i0000001i"), "i0000002");
+        assert_eq!(rank_after("zzzzzzzz"), "zzzzzzzzi");
+        let mut rank = "i".to_string();
+        for _ in 0..1000 {
+            let next = rank_after(&rank);
+            assert!(rank < next);
+            rank = next;
+        }
+        assert!(rank.len() <= 8);
+    }
+
+    #[test]
+    fn small_test_priority_should_be_in_range() {
+        assert!(check_priority(None).is_ok());
+        assert!(check_priority(Some(MAX_PRIORITY)).is_ok());
+        assert!(check_priority(Some(-1)).is_err());
+        assert!(check_priority(Some(MAX_PRIORITY + 1)).is_err());
+    }
+
+    #[tokio::test]
+    async fn small_test_activity_filter_from_query() {
+        let filter = warp::test::request()
+            .path("/?status=in_progress&project_id=2&has_remaining=true&sort=due_at&order=desc&search=%20100%25%20")
+            .filter(&warp::query::<ActivityFilter>())
+            .await
+            .unwrap()
+            .validate()
+            .unwrap();
+        assert_eq!(filter.status, Some(ActivityStatus::InProgress));
+        assert_eq!(filter.project_id, Some(2));
+        assert_eq!(filter.has_remaining, Some(true));
+        assert_eq!(filter.search_pattern().unwrap(), "%100\\%%");
+        assert_eq!(
+            filter.sort.unwrap().order_by(filter.order),
+            "due_at DESC NULLS LAST, rank, id"
+        );
+
+        let result = warp::test::request()
+            .path("/?sort=id%3BDROP%20TABLE%20activities")
+            .filter(&warp::query::<ActivityFilter>())
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn small_test_activity_filter_should_have_valid_due_range() {
+        let from: DateTime<Utc> = "2026-10-18T09:00:00Z".parse().unwrap();
+        let filter = ActivityFilter {
+            due_from: Some(from),
+            due_to: Some(from),
+            ..Default::default()
+        };
+        assert!(filter.validate().is_err());
+        let filter = ActivityFilter {
+            search: Some("  ".to_string()),
+            ..Default::default()
+        }
+        .validate()
+        .unwrap();
+        assert_eq!(filter.search, None);
+        assert_eq!(
+            ActivitySort::Priority.order_by(None),
+            "priority DESC, rank, id"
+        );
+    }
 }
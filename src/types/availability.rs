@@ -0,0 +1,80 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::types::recurrence::Day;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleKind {
+    Work,
+    Break,
+}
+
+impl RuleKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleKind::Work => "work",
+            RuleKind::Break => "break",
+        }
+    }
+}
+
+/// Weekly working hours or a break inside of them, in the account time zone.
+/// Days without work rules are days off.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct AvailabilityRule {
+    pub id: i32,
+    pub weekday: Day,
+    pub kind: RuleKind,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct NewAvailabilityRule {
+    pub weekday: Day,
+    pub kind: RuleKind,
+    pub start_time: NaiveTime,
+    pub end_time: NaiveTime,
+}
+
+impl NewAvailabilityRule {
+    pub fn validate(&self) -> Result<(), handle_errors::Error> {
+        if self.end_time <= self.start_time {
+            return Err(handle_errors::Error::InvalidTimeRange);
+        }
+        Ok(())
+    }
+}
+
+/// Vacation, sick leave or any other range the account is not available
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct TimeOff {
+    pub id: i32,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct NewTimeOff {
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub reason: Option<String>,
+}
+
+impl NewTimeOff {
+    pub fn validate(&self) -> Result<(), handle_errors::Error> {
+        if self.ends_at <= self.starts_at {
+            return Err(handle_errors::Error::InvalidTimeRange);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, ToSchema)]
+pub struct Availability {
+    pub rules: Vec<AvailabilityRule>,
+    pub time_off: Vec<TimeOff>,
+}
@@ -0,0 +1,36 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::types::activities::ActivityId;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, Hash, PartialEq, ToSchema)]
+pub struct TimeEntryId(pub i32);
+
+/// One booked working session on an activity, `duration` is in seconds
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct TimeEntry {
+    pub id: TimeEntryId,
+    pub activity_id: ActivityId,
+    pub started_at: DateTime<Utc>,
+    pub stopped_at: DateTime<Utc>,
+    pub duration: i32,
+    /// Duration was capped by the server, e.g. for an abandoned timer
+    pub needs_review: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct NewTimeEntry {
+    pub activity_id: ActivityId,
+    pub started_at: DateTime<Utc>,
+    pub stopped_at: DateTime<Utc>,
+    pub duration: i32,
+    pub needs_review: bool,
+}
+
+/// Time logged by hand, the duration is the whole range
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct TimeEntryRange {
+    pub started_at: DateTime<Utc>,
+    pub stopped_at: DateTime<Utc>,
+}
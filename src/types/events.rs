@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::types::account::AccountID;
+use crate::types::activities::{Activity, ActivityId};
+use crate::types::entries::TimeEntry;
+use crate::types::timer::TimerStatus;
+
+/// Change pushed to the clients of an account
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    TimerStarted {
+        timer: TimerStatus,
+    },
+    TimerPaused {
+        timer: TimerStatus,
+    },
+    TimerResumed {
+        timer: TimerStatus,
+    },
+    TimerStopped {
+        time_entry: TimeEntry,
+    },
+    ActivityCreated {
+        activity: Activity,
+    },
+    ActivityUpdated {
+        activity: Activity,
+    },
+    ActivityDeleted {
+        activity_id: ActivityId,
+    },
+    /// The stream fell behind and `missed` events were dropped, state should be fetched again
+    Resync {
+        missed: u64,
+    },
+}
+
+impl Event {
+    /// Name of the SSE event, the same as the `type` field
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::TimerStarted { .. } => "timer_started",
+            Event::TimerPaused { .. } => "timer_paused",
+            Event::TimerResumed { .. } => "timer_resumed",
+            Event::TimerStopped { .. } => "timer_stopped",
+            Event::ActivityCreated { .. } => "activity_created",
+            Event::ActivityUpdated { .. } => "activity_updated",
+            Event::ActivityDeleted { .. } => "activity_deleted",
+            Event::Resync { .. } => "resync",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AccountEvent {
+    pub account_id: AccountID,
+    pub event: Event,
+}
+
+#[cfg(test)]
+mod test_events {
+    use super::*;
+
+    #[test]
+    fn small_test_event_type_is_event_name() {
+        let event = Event::ActivityDeleted {
+            activity_id: ActivityId(1),
+        };
+        let value = serde_json::to_value(&event).unwrap();
+        assert_eq!(value["type"], event.name());
+        assert_eq!(value["activity_id"], 1);
+    }
+}
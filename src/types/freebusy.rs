@@ -0,0 +1,15 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::schedule::Interval;
+
+/// Busy and free time of an account without any activity details.
+/// Free time is inside of the working hours when the account has them.
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct FreeBusy {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub busy: Vec<Interval>,
+    pub free: Vec<Interval>,
+}
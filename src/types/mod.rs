@@ -1,3 +1,16 @@
 pub mod account;
 pub mod activities;
+pub mod availability;
+pub mod entries;
+pub mod events;
+pub mod freebusy;
 pub mod pagination;
+pub mod plan;
+pub mod preferences;
+pub mod projects;
+pub mod recurrence;
+pub mod revisions;
+pub mod search;
+pub mod tags;
+pub mod timer;
+pub mod trash;
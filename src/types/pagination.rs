@@ -1,11 +1,210 @@
-use serde::Deserialize;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
 use utoipa::IntoParams;
 
-#[derive(Debug, Deserialize, IntoParams)]
+use crate::types::activities::{Activity, ActivitySort, SortOrder};
+
+pub const DEFAULT_LIMIT: i32 = 50;
+pub const MAX_LIMIT: i32 = 200;
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
 #[into_params(parameter_in = Query)]
 pub struct Pagination {
+    /// Page size, from 1 to 200, 50 by default
     #[param(inline)]
     pub limit: Option<i32>,
     #[param(inline)]
     pub offset: Option<i32>,
+    /// Opaque position from the `Link` header, can't be combined with offset
+    #[param(inline)]
+    pub cursor: Option<String>,
+}
+
+impl Pagination {
+    /// Fills the default page size, the limit and offset should fit the bounds
+    pub fn validate(self) -> Result<Self, handle_errors::Error> {
+        let limit = self.limit.unwrap_or(DEFAULT_LIMIT);
+        if !(1..=MAX_LIMIT).contains(&limit) || self.offset.is_some_and(|offset| offset < 0) {
+            return Err(handle_errors::Error::MissingParameters);
+        }
+        if self.cursor.is_some() && self.offset.is_some() {
+            return Err(handle_errors::Error::InvalidCursor);
+        }
+        Ok(Pagination {
+            limit: Some(limit),
+            ..self
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    Next,
+    Prev,
+}
+
+/// Position right after (or before) an activity in one list order
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Cursor {
+    pub direction: Direction,
+    pub sort: ActivitySort,
+    pub order: SortOrder,
+    pub value: Option<String>,
+    pub rank: String,
+    pub id: i32,
+}
+
+impl Cursor {
+    pub fn new(
+        direction: Direction,
+        sort: ActivitySort,
+        order: SortOrder,
+        activity: &Activity,
+    ) -> Self {
+        Cursor {
+            direction,
+            sort,
+            order,
+            value: sort.value(activity),
+            rank: activity.rank.clone(),
+            id: activity.id.0,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    /// The cursor should come from a list with the same order
+    pub fn decode(
+        cursor: &str,
+        sort: ActivitySort,
+        order: SortOrder,
+    ) -> Result<Self, handle_errors::Error> {
+        URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|json| serde_json::from_slice::<Cursor>(&json).ok())
+            .filter(|cursor| cursor.sort == sort && cursor.order == order)
+            .ok_or(handle_errors::Error::InvalidCursor)
+    }
+
+    /// Sort values of the cursor row, in the order of `ActivitySort::keys`
+    pub fn values(&self) -> Vec<Option<String>> {
+        let mut values = match self.sort {
+            ActivitySort::Rank => vec![],
+            _ => vec![self.value.clone()],
+        };
+        values.push(Some(self.rank.clone()));
+        values.push(Some(self.id.to_string()));
+        values
+    }
+}
+
+/// RFC 8288 links to the neighbour pages, the other query parameters are kept
+pub fn link_header(
+    path: &str,
+    query: &str,
+    next: Option<&Cursor>,
+    prev: Option<&Cursor>,
+) -> Option<String> {
+    let params: Vec<&str> = query
+        .split('&')
+        .filter(|param| {
+            let name = param.split('=').next().unwrap_or_default();
+            !param.is_empty() && name != "cursor" && name != "offset"
+        })
+        .collect();
+    let links: Vec<String> = [(next, "next"), (prev, "prev")]
+        .into_iter()
+        .filter_map(|(cursor, rel)| {
+            let cursor = format!("cursor={}", cursor?.encode());
+            let query = params
+                .iter()
+                .copied()
+                .chain([cursor.as_str()])
+                .collect::<Vec<&str>>()
+                .join("&");
+            Some(format!("<{path}?{query}>; rel=\"{rel}\""))
+        })
+        .collect();
+    (!links.is_empty()).then(|| links.join(", "))
+}
+
+#[cfg(test)]
+mod test_pagination {
+    use super::*;
+
+    #[test]
+    fn small_test_pagination_should_be_bounded() {
+        let page = Pagination::default().validate().unwrap();
+        assert_eq!(page.limit, Some(DEFAULT_LIMIT));
+        for (limit, offset) in [
+            (Some(0), None),
+            (Some(MAX_LIMIT + 1), None),
+            (None, Some(-1)),
+        ] {
+            let page = Pagination {
+                limit,
+                offset,
+                cursor: None,
+            };
+            assert!(page.validate().is_err());
+        }
+        let page = Pagination {
+            limit: None,
+            offset: Some(10),
+            cursor: Some("x".to_string()),
+        };
+        assert!(page.validate().is_err());
+    }
+
+    #[test]
+    fn small_test_cursor_round_trip() {
+        let cursor = Cursor {
+            direction: Direction::Next,
+            sort: ActivitySort::DueAt,
+            order: SortOrder::Desc,
+            value: None,
+            rank: "i0000001".to_string(),
+            id: 42,
+        };
+        let encoded = cursor.encode();
+        assert!(encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        let decoded = Cursor::decode(&encoded, ActivitySort::DueAt, SortOrder::Desc).unwrap();
+        assert_eq!(decoded, cursor);
+        assert_eq!(decoded.values().len(), 3);
+        assert!(Cursor::decode(&encoded, ActivitySort::DueAt, SortOrder::Asc).is_err());
+        assert!(Cursor::decode("not a cursor", ActivitySort::Rank, SortOrder::Asc).is_err());
+    }
+
+    #[test]
+    fn small_test_link_header_replaces_position() {
+        let cursor = Cursor {
+            direction: Direction::Next,
+            sort: ActivitySort::Rank,
+            order: SortOrder::Asc,
+            value: None,
+            rank: "i".to_string(),
+            id: 1,
+        };
+        let link = link_header(
+            "/v1/activity",
+            "status=todo&offset=50&limit=10&cursor=old",
+            Some(&cursor),
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            link,
+            format!(
+                "</v1/activity?status=todo&limit=10&cursor={}>; rel=\"next\"",
+                cursor.encode()
+            )
+        );
+        assert_eq!(link_header("/v1/activity", "", None, None), None);
+    }
 }
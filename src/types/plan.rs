@@ -0,0 +1,59 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::types::activities::ActivityId;
+
+/// Day to plan, today by default, working hours are in the account time zone.
+/// Without them the account availability is used, 09:00-17:00 when the account
+/// has no working hours.
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PlanQuery {
+    #[param(inline)]
+    pub date: Option<NaiveDate>,
+    #[param(inline)]
+    pub work_start: Option<NaiveTime>,
+    #[param(inline)]
+    pub work_end: Option<NaiveTime>,
+}
+
+impl PlanQuery {
+    pub fn default_hours() -> (NaiveTime, NaiveTime) {
+        (
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+            NaiveTime::from_hms_opt(17, 0, 0).unwrap(),
+        )
+    }
+
+    /// Working hours given in the query, empty when the query has none
+    pub fn working_hours(&self) -> Result<Option<(NaiveTime, NaiveTime)>, handle_errors::Error> {
+        if self.work_start.is_none() && self.work_end.is_none() {
+            return Ok(None);
+        }
+        let (start, end) = PlanQuery::default_hours();
+        let start = self.work_start.unwrap_or(start);
+        let end = self.work_end.unwrap_or(end);
+        if end <= start {
+            return Err(handle_errors::Error::InvalidTimeRange);
+        }
+        Ok(Some((start, end)))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct PlannedBlock {
+    pub activity_id: ActivityId,
+    #[serde(default)]
+    pub title: String,
+    pub scheduled_start: DateTime<Utc>,
+    pub scheduled_end: DateTime<Utc>,
+}
+
+/// Proposed schedule, `unplaced` activities didn't fit into the free time
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Plan {
+    pub date: NaiveDate,
+    pub blocks: Vec<PlannedBlock>,
+    pub unplaced: Vec<ActivityId>,
+}
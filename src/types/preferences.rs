@@ -0,0 +1,136 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::types::recurrence::Day;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSchema)]
+pub enum TimeFormat {
+    #[serde(rename = "24h")]
+    H24,
+    #[serde(rename = "12h")]
+    H12,
+}
+
+impl TimeFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeFormat::H24 => "24h",
+            TimeFormat::H12 => "12h",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct Preferences {
+    /// Starting a timer stops every other timer of the account
+    pub single_active_timer: bool,
+    /// IANA time zone, days of plans, recurrences and working hours follow it
+    pub timezone: String,
+    pub week_start: Day,
+    /// Estimate in minutes for activities added without `time`
+    pub default_activity_duration: i32,
+    pub time_format: TimeFormat,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Preferences {
+            single_active_timer: false,
+            timezone: "UTC".to_string(),
+            week_start: Day::Mon,
+            default_activity_duration: 30,
+            time_format: TimeFormat::H24,
+        }
+    }
+}
+
+impl Preferences {
+    pub fn tz(&self) -> Tz {
+        self.timezone.parse().unwrap_or(Tz::UTC)
+    }
+
+    pub fn week_start(&self) -> Weekday {
+        self.week_start.into()
+    }
+
+    pub fn validate(&self) -> Result<(), handle_errors::Error> {
+        if self.timezone.parse::<Tz>().is_err() {
+            return Err(handle_errors::Error::InvalidTimeZone);
+        }
+        if self.default_activity_duration <= 0 {
+            return Err(handle_errors::Error::MissingParameters);
+        }
+        Ok(())
+    }
+}
+
+/// Wall clock time of `tz` as UTC, times skipped by a DST gap move an hour forward
+pub fn local_to_utc(tz: &Tz, local: NaiveDateTime) -> DateTime<Utc> {
+    tz.from_local_datetime(&local)
+        .earliest()
+        .or_else(|| {
+            tz.from_local_datetime(&(local + TimeDelta::hours(1)))
+                .earliest()
+        })
+        .map(|time| time.with_timezone(&Utc))
+        .unwrap_or_else(|| local.and_utc())
+}
+
+/// Bounds of the local `date` in `tz`, `[midnight, next midnight)`
+pub fn day_bounds(tz: &Tz, date: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    (
+        local_to_utc(tz, date.and_time(Default::default())),
+        local_to_utc(
+            tz,
+            date.succ_opt().unwrap_or(date).and_time(Default::default()),
+        ),
+    )
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct PartialPreferences {
+    pub single_active_timer: Option<bool>,
+    pub timezone: Option<String>,
+    pub week_start: Option<Day>,
+    pub default_activity_duration: Option<i32>,
+    pub time_format: Option<TimeFormat>,
+}
+
+#[cfg(test)]
+mod test_preferences {
+    use super::*;
+
+    #[test]
+    fn small_test_timezone_should_be_iana_name() {
+        let mut preferences = Preferences {
+            timezone: "Europe/Berlin".to_string(),
+            ..Default::default()
+        };
+        assert!(preferences.validate().is_ok());
+        assert_eq!(preferences.tz(), Tz::Europe__Berlin);
+        preferences.timezone = "Mars/Olympus".to_string();
+        assert!(preferences.validate().is_err());
+        assert_eq!(preferences.tz(), Tz::UTC);
+    }
+
+    #[test]
+    fn small_test_local_day_should_follow_dst() {
+        let tz = Tz::Europe__Berlin;
+        let (start, end) = day_bounds(&tz, "2026-10-25".parse().unwrap());
+        assert_eq!(
+            start,
+            "2026-10-24T22:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(
+            end,
+            "2026-10-25T23:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        let gap = "2026-03-29T02:30:00".parse().unwrap();
+        assert_eq!(
+            local_to_utc(&tz, gap),
+            "2026-03-29T01:30:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+    }
+}
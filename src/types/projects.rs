@@ -0,0 +1,125 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::types::activities::Activity;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, Hash, PartialEq, ToSchema)]
+pub struct ProjectId(pub i32);
+
+/// Times of the project activities or of an activity subtree summed up, in seconds
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+pub struct Rollup {
+    pub time: i64,
+    pub spent: i64,
+    pub remaining: i64,
+    pub overtime: i64,
+}
+
+impl Rollup {
+    pub fn of(activity: &Activity) -> Self {
+        Rollup {
+            time: activity.time as i64,
+            spent: activity.spent as i64,
+            remaining: activity.remaining as i64,
+            overtime: activity.overtime as i64,
+        }
+    }
+
+    pub fn add(&mut self, other: Rollup) {
+        self.time += other.time;
+        self.spent += other.spent;
+        self.remaining += other.remaining;
+        self.overtime += other.overtime;
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Project {
+    pub id: ProjectId,
+    pub title: String,
+    pub content: String,
+    pub created_on: DateTime<Utc>,
+    pub rollup: Rollup,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct NewProject {
+    pub title: String,
+    #[serde(default)]
+    pub content: String,
+}
+
+impl NewProject {
+    pub fn validate(&self) -> Result<(), handle_errors::Error> {
+        if self.title.trim().is_empty() {
+            return Err(handle_errors::Error::MissingParameters);
+        }
+        Ok(())
+    }
+}
+
+/// Activity with its children, `rollup` covers the activity and all of its descendants
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct ActivityTree {
+    pub activity: Activity,
+    pub rollup: Rollup,
+    #[schema(no_recursion)]
+    pub children: Vec<ActivityTree>,
+}
+
+impl ActivityTree {
+    /// Builds the tree of `root` out of its descendants, in any order
+    pub fn build(root: Activity, descendants: &[Activity]) -> Self {
+        let children: Vec<ActivityTree> = descendants
+            .iter()
+            .filter(|activity| activity.parent_id.as_ref() == Some(&root.id))
+            .map(|child| ActivityTree::build(child.clone(), descendants))
+            .collect();
+        let mut rollup = Rollup::of(&root);
+        for child in &children {
+            rollup.add(child.rollup);
+        }
+        ActivityTree {
+            activity: root,
+            rollup,
+            children,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_projects {
+    use super::*;
+    use crate::tests::helpers::test_activity;
+    use crate::types::activities::ActivityId;
+
+    fn activity(id: i32, parent_id: Option<i32>, time: i32, spent: i32) -> Activity {
+        Activity {
+            parent_id: parent_id.map(ActivityId),
+            ..test_activity(id, time, spent)
+        }
+    }
+
+    #[test]
+    fn small_test_tree_rolls_up_children() {
+        let descendants = vec![
+            activity(3, Some(2), 60, 90),
+            activity(2, Some(1), 60, 0),
+            activity(4, Some(1), 60, 30),
+        ];
+        let tree = ActivityTree::build(activity(1, None, 100, 0), &descendants);
+        assert_eq!(tree.children.len(), 2);
+        assert_eq!(
+            tree.children[0].rollup,
+            Rollup {
+                time: 120,
+                spent: 90,
+                remaining: 60,
+                overtime: 30,
+            }
+        );
+        assert_eq!(tree.rollup.time, 280);
+        assert_eq!(tree.rollup.remaining, 190);
+    }
+}
@@ -0,0 +1,495 @@
+use std::str::FromStr;
+
+use chrono::prelude::*;
+use chrono::{Days, Months, TimeDelta};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::types::{activities::ActivityId, preferences::local_to_utc};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Frequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Frequency::Daily => "daily",
+            Frequency::Weekly => "weekly",
+            Frequency::Monthly => "monthly",
+        }
+    }
+}
+
+impl FromStr for Frequency {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "daily" => Ok(Frequency::Daily),
+            "weekly" => Ok(Frequency::Weekly),
+            "monthly" => Ok(Frequency::Monthly),
+            _ => Err(format!("unknown frequency {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Day {
+    Mon,
+    Tue,
+    Wed,
+    Thu,
+    Fri,
+    Sat,
+    Sun,
+}
+
+impl From<Day> for Weekday {
+    fn from(day: Day) -> Self {
+        match day {
+            Day::Mon => Weekday::Mon,
+            Day::Tue => Weekday::Tue,
+            Day::Wed => Weekday::Wed,
+            Day::Thu => Weekday::Thu,
+            Day::Fri => Weekday::Fri,
+            Day::Sat => Weekday::Sat,
+            Day::Sun => Weekday::Sun,
+        }
+    }
+}
+
+impl From<Weekday> for Day {
+    fn from(day: Weekday) -> Self {
+        match day {
+            Weekday::Mon => Day::Mon,
+            Weekday::Tue => Day::Tue,
+            Weekday::Wed => Day::Wed,
+            Weekday::Thu => Day::Thu,
+            Weekday::Fri => Day::Fri,
+            Weekday::Sat => Day::Sat,
+            Weekday::Sun => Day::Sun,
+        }
+    }
+}
+
+/// Repeat rule of an activity. Occurrences start at the time of day of `starts_at`,
+/// `by_weekday` limits daily and weekly rules, monthly rules repeat the day of month.
+/// `until` and `count` end the rule, dates in `exceptions` are skipped.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct Recurrence {
+    pub activity_id: ActivityId,
+    pub frequency: Frequency,
+    pub interval: i32,
+    pub by_weekday: Vec<Day>,
+    pub starts_at: DateTime<Utc>,
+    pub until: Option<DateTime<Utc>>,
+    pub count: Option<i32>,
+    pub exceptions: Vec<NaiveDate>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct NewRecurrence {
+    pub frequency: Frequency,
+    pub interval: Option<i32>,
+    pub by_weekday: Option<Vec<Day>>,
+    pub starts_at: DateTime<Utc>,
+    pub until: Option<DateTime<Utc>>,
+    pub count: Option<i32>,
+    pub exceptions: Option<Vec<NaiveDate>>,
+}
+
+impl NewRecurrence {
+    /// Longest step between repetitions, in days, weeks or months
+    pub const MAX_INTERVAL: i32 = 1000;
+    /// Most occurrences of a rule ending after a count
+    pub const MAX_COUNT: i32 = 10_000;
+
+    pub fn into_recurrence(self, id: i32) -> Result<Recurrence, handle_errors::Error> {
+        let mut by_weekday = self.by_weekday.unwrap_or_default();
+        by_weekday.sort();
+        by_weekday.dedup();
+        let recurrence = Recurrence {
+            activity_id: ActivityId(id),
+            frequency: self.frequency,
+            interval: self.interval.unwrap_or(1),
+            by_weekday,
+            starts_at: self.starts_at,
+            until: self.until,
+            count: self.count,
+            exceptions: self.exceptions.unwrap_or_default(),
+        };
+        if !(1..=Self::MAX_INTERVAL).contains(&recurrence.interval)
+            || recurrence
+                .count
+                .is_some_and(|count| !(1..=Self::MAX_COUNT).contains(&count))
+            || recurrence
+                .until
+                .is_some_and(|until| until < recurrence.starts_at)
+            || (recurrence.frequency == Frequency::Monthly && !recurrence.by_weekday.is_empty())
+        {
+            return Err(handle_errors::Error::InvalidRecurrence);
+        }
+        Ok(recurrence)
+    }
+}
+
+impl Recurrence {
+    /// First day of the `period`-th repetition after the local `start`,
+    /// `None` once the dates run out of range
+    fn period_start(
+        &self,
+        start: NaiveDate,
+        week_start: Weekday,
+        period: u32,
+    ) -> Option<NaiveDate> {
+        let step = period.checked_mul(self.interval as u32)?;
+        match self.frequency {
+            Frequency::Daily => start.checked_add_days(Days::new(step as u64)),
+            Frequency::Weekly => start
+                .week(week_start)
+                .first_day()
+                .checked_add_days(Days::new(7 * step as u64)),
+            Frequency::Monthly => start.with_day(1)?.checked_add_months(Months::new(step)),
+        }
+    }
+
+    /// Repetition containing the local `day`, the earlier ones end before it
+    fn period_of(&self, start: NaiveDate, week_start: Weekday, day: NaiveDate) -> u32 {
+        let units = match self.frequency {
+            Frequency::Daily => (day - start).num_days(),
+            Frequency::Weekly => {
+                (day.week(week_start).first_day() - start.week(week_start).first_day()).num_days()
+                    / 7
+            }
+            Frequency::Monthly => {
+                (day.year() - start.year()) as i64 * 12 + day.month() as i64 - start.month() as i64
+            }
+        };
+        u32::try_from(units.max(0) / self.interval.max(1) as i64).unwrap_or(u32::MAX)
+    }
+
+    /// Days of the repetition beginning at `first`, in order
+    fn period_days(
+        &self,
+        start: NaiveDate,
+        week_start: Weekday,
+        first: NaiveDate,
+    ) -> Vec<NaiveDate> {
+        let days = match self.frequency {
+            Frequency::Daily if self.by_weekday.is_empty() => vec![first],
+            Frequency::Daily => self
+                .by_weekday
+                .contains(&Day::from(first.weekday()))
+                .then_some(first)
+                .into_iter()
+                .collect(),
+            Frequency::Weekly if self.by_weekday.is_empty() => first
+                .checked_add_days(Days::new(start.weekday().days_since(week_start) as u64))
+                .into_iter()
+                .collect(),
+            Frequency::Weekly => {
+                let mut days: Vec<NaiveDate> = self
+                    .by_weekday
+                    .iter()
+                    .filter_map(|day| {
+                        first.checked_add_days(Days::new(
+                            Weekday::from(*day).days_since(week_start) as u64,
+                        ))
+                    })
+                    .collect();
+                days.sort();
+                days
+            }
+            Frequency::Monthly => first.with_day(start.day()).into_iter().collect(),
+        };
+        days.into_iter().filter(|day| *day >= start).collect()
+    }
+
+    /// Starts of the occurrences inside of `[from, to)`, repeating at the
+    /// same wall clock time of `tz`. Rules without `count` start walking at `from`.
+    pub fn occurrences(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        tz: &Tz,
+        week_start: Weekday,
+    ) -> Vec<DateTime<Utc>> {
+        let local = self.starts_at.with_timezone(tz).naive_local();
+        let (start_day, time) = (local.date(), local.time());
+        // occurrences before `from` only matter to count them
+        let skipped = match self.count {
+            Some(_) => 0,
+            None => self.period_of(start_day, week_start, from.with_timezone(tz).date_naive()),
+        };
+        let mut res = vec![];
+        let mut counted = 0;
+        for period in skipped..=u32::MAX {
+            let Some(first) = self.period_start(start_day, week_start, period) else {
+                break;
+            };
+            if local_to_utc(tz, first.and_time(time)) >= to {
+                break;
+            }
+            for day in self.period_days(start_day, week_start, first) {
+                let start = local_to_utc(tz, day.and_time(time));
+                if start >= to || self.until.is_some_and(|until| start > until) {
+                    return res;
+                }
+                counted += 1;
+                if self.count.is_some_and(|count| counted > count) {
+                    return res;
+                }
+                if start >= from && !self.exceptions.contains(&day) {
+                    res.push(start);
+                }
+            }
+        }
+        res
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct OccurrenceRange {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+impl OccurrenceRange {
+    /// Longest range which can be expanded at once
+    pub const MAX_DAYS: i64 = 366;
+
+    pub fn validate(&self) -> Result<(), handle_errors::Error> {
+        if self.to <= self.from || self.to - self.from > TimeDelta::days(Self::MAX_DAYS) {
+            return Err(handle_errors::Error::InvalidTimeRange);
+        }
+        Ok(())
+    }
+}
+
+/// One planned run of a recurring activity, it lasts the activity estimate
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Occurrence {
+    pub activity_id: ActivityId,
+    pub title: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+#[cfg(test)]
+mod test_recurrence {
+    use super::*;
+
+    fn rule(frequency: Frequency, starts_at: &str) -> Recurrence {
+        Recurrence {
+            activity_id: ActivityId(1),
+            frequency,
+            interval: 1,
+            by_weekday: vec![],
+            starts_at: starts_at.parse().unwrap(),
+            until: None,
+            count: None,
+            exceptions: vec![],
+        }
+    }
+
+    fn at(time: &str) -> DateTime<Utc> {
+        time.parse().unwrap()
+    }
+
+    #[test]
+    fn small_test_every_weekday_skips_weekend() {
+        // 2026-10-16 is a Friday
+        let mut recurrence = rule(Frequency::Daily, "2026-10-16T09:00:00Z");
+        recurrence.by_weekday = vec![Day::Mon, Day::Tue, Day::Wed, Day::Thu, Day::Fri];
+        let res = recurrence.occurrences(
+            at("2026-10-16T00:00:00Z"),
+            at("2026-10-21T00:00:00Z"),
+            &Tz::UTC,
+            Weekday::Mon,
+        );
+        assert_eq!(
+            res,
+            vec![
+                at("2026-10-16T09:00:00Z"),
+                at("2026-10-19T09:00:00Z"),
+                at("2026-10-20T09:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn small_test_weekly_with_interval_and_count() {
+        let mut recurrence = rule(Frequency::Weekly, "2026-10-14T10:00:00Z");
+        recurrence.interval = 2;
+        recurrence.by_weekday = vec![Day::Mon, Day::Wed];
+        recurrence.count = Some(3);
+        let res = recurrence.occurrences(
+            at("2026-10-01T00:00:00Z"),
+            at("2026-12-01T00:00:00Z"),
+            &Tz::UTC,
+            Weekday::Mon,
+        );
+        assert_eq!(
+            res,
+            vec![
+                at("2026-10-14T10:00:00Z"),
+                at("2026-10-26T10:00:00Z"),
+                at("2026-10-28T10:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn small_test_monthly_skips_short_months() {
+        let recurrence = rule(Frequency::Monthly, "2026-01-31T08:00:00Z");
+        let res = recurrence.occurrences(
+            at("2026-01-01T00:00:00Z"),
+            at("2026-06-01T00:00:00Z"),
+            &Tz::UTC,
+            Weekday::Mon,
+        );
+        assert_eq!(
+            res,
+            vec![
+                at("2026-01-31T08:00:00Z"),
+                at("2026-03-31T08:00:00Z"),
+                at("2026-05-31T08:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn small_test_until_and_exceptions() {
+        let mut recurrence = rule(Frequency::Daily, "2026-10-01T07:30:00Z");
+        recurrence.until = Some(at("2026-10-04T07:30:00Z"));
+        recurrence.exceptions = vec!["2026-10-02".parse().unwrap()];
+        let res = recurrence.occurrences(
+            at("2026-10-02T00:00:00Z"),
+            at("2026-10-10T00:00:00Z"),
+            &Tz::UTC,
+            Weekday::Mon,
+        );
+        assert_eq!(
+            res,
+            vec![at("2026-10-03T07:30:00Z"), at("2026-10-04T07:30:00Z")]
+        );
+    }
+
+    #[test]
+    fn small_test_daily_keeps_local_time_across_dst() {
+        let recurrence = rule(Frequency::Daily, "2026-10-24T07:00:00Z");
+        let tz = Tz::Europe__Berlin;
+        let res = recurrence.occurrences(
+            at("2026-10-24T00:00:00Z"),
+            at("2026-10-26T00:00:00Z"),
+            &tz,
+            Weekday::Mon,
+        );
+        assert_eq!(
+            res,
+            vec![at("2026-10-24T07:00:00Z"), at("2026-10-25T08:00:00Z")]
+        );
+    }
+
+    #[test]
+    fn small_test_weekly_interval_follows_week_start() {
+        // 2026-10-18 is a Sunday
+        let mut recurrence = rule(Frequency::Weekly, "2026-10-18T09:00:00Z");
+        recurrence.interval = 2;
+        recurrence.by_weekday = vec![Day::Sun, Day::Mon];
+        let from = at("2026-10-18T00:00:00Z");
+        let to = at("2026-11-03T00:00:00Z");
+        assert_eq!(
+            recurrence.occurrences(from, to, &Tz::UTC, Weekday::Sun),
+            vec![
+                at("2026-10-18T09:00:00Z"),
+                at("2026-10-19T09:00:00Z"),
+                at("2026-11-01T09:00:00Z"),
+                at("2026-11-02T09:00:00Z"),
+            ]
+        );
+        assert_eq!(
+            recurrence.occurrences(from, to, &Tz::UTC, Weekday::Mon),
+            vec![
+                at("2026-10-18T09:00:00Z"),
+                at("2026-10-26T09:00:00Z"),
+                at("2026-11-01T09:00:00Z"),
+            ]
+        );
+    }
+
+    #[test]
+    fn small_test_old_rule_starts_walking_at_range() {
+        let mut rules = vec![
+            rule(Frequency::Daily, "1990-03-05T09:00:00Z"),
+            rule(Frequency::Weekly, "1990-03-07T09:00:00Z"),
+            rule(Frequency::Monthly, "1990-03-15T09:00:00Z"),
+        ];
+        rules[0].interval = 3;
+        rules[1].interval = 2;
+        rules[1].by_weekday = vec![Day::Mon, Day::Wed];
+        rules[2].interval = 5;
+        let from = at("2026-10-16T12:00:00Z");
+        let to = at("2027-10-16T00:00:00Z");
+        for recurrence in rules {
+            // a count makes the walk start at `starts_at`
+            let walked = Recurrence {
+                count: Some(i32::MAX),
+                ..recurrence.clone()
+            };
+            let res = recurrence.occurrences(from, to, &Tz::Europe__Berlin, Weekday::Mon);
+            assert!(!res.is_empty());
+            assert_eq!(
+                res,
+                walked.occurrences(from, to, &Tz::Europe__Berlin, Weekday::Mon)
+            );
+        }
+    }
+
+    #[test]
+    fn small_test_walk_stops_at_last_date() {
+        let mut recurrence = rule(Frequency::Monthly, "2026-10-01T00:00:00Z");
+        recurrence.starts_at = DateTime::<Utc>::MAX_UTC - TimeDelta::days(40);
+        recurrence.interval = NewRecurrence::MAX_INTERVAL;
+        let res = recurrence.occurrences(
+            recurrence.starts_at - TimeDelta::days(1),
+            DateTime::<Utc>::MAX_UTC,
+            &Tz::UTC,
+            Weekday::Mon,
+        );
+        assert_eq!(res.len(), 1);
+    }
+
+    #[test]
+    fn small_test_invalid_rule_is_rejected() {
+        let new = NewRecurrence {
+            frequency: Frequency::Monthly,
+            interval: Some(0),
+            by_weekday: None,
+            starts_at: at("2026-10-01T07:30:00Z"),
+            until: None,
+            count: None,
+            exceptions: None,
+        };
+        assert!(new.clone().into_recurrence(1).is_err());
+        let new = NewRecurrence {
+            interval: Some(NewRecurrence::MAX_INTERVAL + 1),
+            ..new
+        };
+        assert!(new.clone().into_recurrence(1).is_err());
+        let new = NewRecurrence {
+            interval: Some(NewRecurrence::MAX_INTERVAL),
+            count: Some(NewRecurrence::MAX_COUNT + 1),
+            ..new
+        };
+        assert!(new.into_recurrence(1).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+use std::str::FromStr;
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::types::account::AccountID;
+use crate::types::activities::{Activity, ActivityId, ActivityStatus};
+use crate::types::projects::ProjectId;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RevisionKind {
+    #[default]
+    Update,
+    TimerStopped,
+    EntryAdded,
+    EntryUpdated,
+    EntryDeleted,
+    Delete,
+    Revert,
+}
+
+impl RevisionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RevisionKind::Update => "update",
+            RevisionKind::TimerStopped => "timer_stopped",
+            RevisionKind::EntryAdded => "entry_added",
+            RevisionKind::EntryUpdated => "entry_updated",
+            RevisionKind::EntryDeleted => "entry_deleted",
+            RevisionKind::Delete => "delete",
+            RevisionKind::Revert => "revert",
+        }
+    }
+}
+
+impl FromStr for RevisionKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "update" => Ok(RevisionKind::Update),
+            "timer_stopped" => Ok(RevisionKind::TimerStopped),
+            "entry_added" => Ok(RevisionKind::EntryAdded),
+            "entry_updated" => Ok(RevisionKind::EntryUpdated),
+            "entry_deleted" => Ok(RevisionKind::EntryDeleted),
+            "delete" => Ok(RevisionKind::Delete),
+            "revert" => Ok(RevisionKind::Revert),
+            _ => Err(format!("unknown revision kind {}", s)),
+        }
+    }
+}
+
+/// Fields of an activity kept in its history
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, ToSchema)]
+#[serde(default)]
+pub struct ActivitySnapshot {
+    pub title: String,
+    pub content: String,
+    pub time: i32,
+    pub spent: i32,
+    pub scheduled_start: Option<DateTime<Utc>>,
+    pub scheduled_end: Option<DateTime<Utc>>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub project_id: Option<ProjectId>,
+    pub parent_id: Option<ActivityId>,
+    pub priority: i16,
+    pub status: ActivityStatus,
+}
+
+impl From<&Activity> for ActivitySnapshot {
+    fn from(activity: &Activity) -> Self {
+        ActivitySnapshot {
+            title: activity.title.clone(),
+            content: activity.content.clone(),
+            time: activity.time,
+            spent: activity.spent,
+            scheduled_start: activity.scheduled_start,
+            scheduled_end: activity.scheduled_end,
+            due_at: activity.due_at,
+            project_id: activity.project_id,
+            parent_id: activity.parent_id.clone(),
+            priority: activity.priority,
+            status: activity.status,
+        }
+    }
+}
+
+impl ActivitySnapshot {
+    /// Names of the fields which differ in the other snapshot
+    pub fn changes(&self, other: &ActivitySnapshot) -> Vec<String> {
+        let fields = [
+            ("title", self.title != other.title),
+            ("content", self.content != other.content),
+            ("time", self.time != other.time),
+            ("spent", self.spent != other.spent),
+            (
+                "scheduled_start",
+                self.scheduled_start != other.scheduled_start,
+            ),
+            ("scheduled_end", self.scheduled_end != other.scheduled_end),
+            ("due_at", self.due_at != other.due_at),
+            ("project_id", self.project_id != other.project_id),
+            ("parent_id", self.parent_id != other.parent_id),
+            ("priority", self.priority != other.priority),
+            ("status", self.status != other.status),
+        ];
+        fields
+            .into_iter()
+            .filter(|(_, changed)| *changed)
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    /// Undoes the edits made between this snapshot and `after`, later edits of other
+    /// fields stay. Booked time and status are not edits, they keep their own history.
+    pub fn undo(self, after: &ActivitySnapshot, mut activity: Activity) -> Activity {
+        if self.title != after.title {
+            activity.title = self.title;
+        }
+        if self.content != after.content {
+            activity.content = self.content;
+        }
+        if self.time != after.time {
+            activity.time = self.time;
+        }
+        if self.scheduled_start != after.scheduled_start {
+            activity.scheduled_start = self.scheduled_start;
+        }
+        if self.scheduled_end != after.scheduled_end {
+            activity.scheduled_end = self.scheduled_end;
+        }
+        if self.due_at != after.due_at {
+            activity.due_at = self.due_at;
+        }
+        if self.project_id != after.project_id {
+            activity.project_id = self.project_id;
+        }
+        if self.parent_id != after.parent_id {
+            activity.parent_id = self.parent_id;
+        }
+        if self.priority != after.priority {
+            activity.priority = self.priority;
+        }
+        activity
+    }
+}
+
+/// Recorded change of an activity, revisions are numbered from 1
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Revision {
+    pub rev: i32,
+    pub kind: RevisionKind,
+    pub changed_by: AccountID,
+    pub changed_on: DateTime<Utc>,
+    /// Revision undone by this one
+    pub reverts: Option<i32>,
+    /// Names of the fields which changed
+    pub changes: Vec<String>,
+    pub before: ActivitySnapshot,
+    pub after: ActivitySnapshot,
+}
+
+impl Revision {
+    /// Only edits can be undone. Booked time goes back by editing or deleting the entry,
+    /// deleted activities come back from the trash.
+    pub fn can_revert(&self) -> bool {
+        matches!(self.kind, RevisionKind::Update | RevisionKind::Revert)
+    }
+}
+
+#[cfg(test)]
+mod test_revisions {
+    use super::*;
+    use crate::tests::helpers::test_activity;
+
+    fn snapshot() -> ActivitySnapshot {
+        ActivitySnapshot {
+            title: "report".to_string(),
+            content: "quarterly".to_string(),
+            time: 60,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn small_test_snapshot_changes_name_fields() {
+        let before = snapshot();
+        assert!(before.changes(&before.clone()).is_empty());
+        let after = ActivitySnapshot {
+            time: 90,
+            spent: 30,
+            due_at: Some(Utc::now()),
+            ..before.clone()
+        };
+        assert_eq!(before.changes(&after), vec!["time", "spent", "due_at"]);
+    }
+
+    #[test]
+    fn small_test_undo_keeps_later_edits_and_booked_time() {
+        let before = snapshot();
+        let after = ActivitySnapshot {
+            time: 90,
+            ..before.clone()
+        };
+        let activity = Activity {
+            title: "report v2".to_string(),
+            content: "quarterly".to_string(),
+            status: ActivityStatus::InProgress,
+            priority: 2,
+            ..test_activity(1, 90, 30)
+        };
+        let activity = before.undo(&after, activity);
+        assert_eq!(activity.time, 60);
+        assert_eq!(activity.title, "report v2");
+        assert_eq!(activity.priority, 2);
+        assert_eq!(activity.spent, 30);
+        assert_eq!(activity.status, ActivityStatus::InProgress);
+    }
+
+    #[test]
+    fn small_test_only_edits_can_be_reverted() {
+        let revision = |kind| Revision {
+            rev: 1,
+            kind,
+            changed_by: AccountID(1),
+            changed_on: Utc::now(),
+            reverts: None,
+            changes: vec![],
+            before: snapshot(),
+            after: snapshot(),
+        };
+        assert!(revision(RevisionKind::Update).can_revert());
+        assert!(revision(RevisionKind::Revert).can_revert());
+        assert!(!revision(RevisionKind::TimerStopped).can_revert());
+        assert!(!revision(RevisionKind::EntryDeleted).can_revert());
+        assert!(!revision(RevisionKind::Delete).can_revert());
+    }
+
+    #[test]
+    fn small_test_snapshot_keeps_old_fields_readable() {
+        let snapshot: ActivitySnapshot = serde_json::from_str(r#"{"title":"report"}"#).unwrap();
+        assert_eq!(snapshot.title, "report");
+        assert_eq!(snapshot.status, ActivityStatus::Todo);
+        for kind in [
+            RevisionKind::Update,
+            RevisionKind::TimerStopped,
+            RevisionKind::EntryAdded,
+            RevisionKind::EntryUpdated,
+            RevisionKind::EntryDeleted,
+            RevisionKind::Delete,
+            RevisionKind::Revert,
+        ] {
+            assert_eq!(kind.as_str().parse::<RevisionKind>().unwrap(), kind);
+        }
+        assert!("rename".parse::<RevisionKind>().is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::types::activities::Activity;
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchQuery {
+    /// Words to look for in the title and content, supports "quotes", `or` and `-word`
+    #[param(inline)]
+    pub q: String,
+}
+
+impl SearchQuery {
+    pub const MAX_QUERY: usize = 256;
+
+    /// Trims the query, it should be non-empty and short enough to parse
+    pub fn validate(mut self) -> Result<Self, handle_errors::Error> {
+        self.q = self.q.trim().to_string();
+        if self.q.is_empty() || self.q.chars().count() > Self::MAX_QUERY {
+            return Err(handle_errors::Error::MissingParameters);
+        }
+        Ok(self)
+    }
+}
+
+/// Found activity, best matches come first
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct SearchHit {
+    pub activity: Activity,
+    pub rank: f32,
+    /// Title with the found words wrapped in `<mark>`
+    pub title: String,
+    /// Parts of the content around the found words
+    pub snippet: String,
+}
+
+#[cfg(test)]
+mod test_search {
+    use super::*;
+
+    #[test]
+    fn small_test_search_query_is_trimmed_and_checked() {
+        let query = SearchQuery {
+            q: " report ".to_string(),
+        };
+        assert_eq!(query.validate().unwrap().q, "report");
+        let query = SearchQuery {
+            q: "  ".to_string(),
+        };
+        assert!(query.validate().is_err());
+        let query = SearchQuery {
+            q: "x".repeat(SearchQuery::MAX_QUERY + 1),
+        };
+        assert!(query.validate().is_err());
+    }
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Label owned by an account, activities can have any number of them
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct Tag {
+    pub id: i32,
+    pub name: String,
+    pub color: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct NewTag {
+    pub name: String,
+    pub color: Option<String>,
+}
+
+impl NewTag {
+    pub const MAX_NAME: usize = 64;
+
+    /// Trims the name, it should be non-empty and fit into the column
+    pub fn validate(mut self) -> Result<Self, handle_errors::Error> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() || self.name.chars().count() > Self::MAX_NAME {
+            return Err(handle_errors::Error::MissingParameters);
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod test_tags {
+    use super::*;
+
+    #[test]
+    fn small_test_tag_name_is_trimmed_and_checked() {
+        let tag = NewTag {
+            name: "  client-a ".to_string(),
+            color: None,
+        };
+        assert_eq!(tag.validate().unwrap().name, "client-a");
+        let tag = NewTag {
+            name: "   ".to_string(),
+            color: None,
+        };
+        assert!(tag.validate().is_err());
+        let tag = NewTag {
+            name: "x".repeat(NewTag::MAX_NAME + 1),
+            color: None,
+        };
+        assert!(tag.validate().is_err());
+    }
+}
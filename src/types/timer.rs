@@ -0,0 +1,457 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::types::activities::{Activity, ActivityId};
+use crate::types::entries::TimeEntry;
+
+/// Timer kept in the cache while an activity is tracked
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct TimerState {
+    pub started_at: DateTime<Utc>,
+    /// Start of the running segment, empty while the timer is paused
+    pub resumed_at: Option<DateTime<Utc>>,
+    /// Seconds tracked by the already finished segments
+    pub accumulated: i64,
+    #[serde(default)]
+    pub pomodoro: Option<PomodoroState>,
+}
+
+impl TimerState {
+    pub fn new(time: DateTime<Utc>) -> Self {
+        TimerState {
+            started_at: time,
+            resumed_at: Some(time),
+            accumulated: 0,
+            pomodoro: None,
+        }
+    }
+
+    pub fn with_pomodoro(time: DateTime<Utc>, settings: Pomodoro) -> Self {
+        TimerState {
+            pomodoro: Some(PomodoroState {
+                settings,
+                phase: PomodoroPhase::Work,
+                cycle: 1,
+            }),
+            ..TimerState::new(time)
+        }
+    }
+
+    /// Seconds to book, breaks of the pomodoro mode are not counted
+    pub fn worked(&self, time: DateTime<Utc>) -> i64 {
+        let elapsed = self.elapsed(time);
+        match &self.pomodoro {
+            Some(pomodoro) => pomodoro.settings.progress(elapsed).worked,
+            None => elapsed,
+        }
+    }
+
+    /// Moves the stored pomodoro phase and cycle to `time`, true when they changed
+    pub fn sync(&mut self, time: DateTime<Utc>) -> bool {
+        let elapsed = self.elapsed(time);
+        match self.pomodoro.as_mut() {
+            Some(pomodoro) => {
+                let progress = pomodoro.settings.progress(elapsed);
+                let changed = pomodoro.phase != progress.phase || pomodoro.cycle != progress.cycle;
+                pomodoro.phase = progress.phase;
+                pomodoro.cycle = progress.cycle;
+                changed
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.resumed_at.is_none()
+    }
+
+    pub fn elapsed(&self, time: DateTime<Utc>) -> i64 {
+        match self.resumed_at {
+            Some(resumed_at) => self.accumulated + (time - resumed_at).num_seconds(),
+            None => self.accumulated,
+        }
+    }
+
+    pub fn pause(&mut self, time: DateTime<Utc>) {
+        self.sync(time);
+        self.accumulated = self.elapsed(time);
+        self.resumed_at = None;
+    }
+
+    pub fn resume(&mut self, time: DateTime<Utc>) {
+        self.resumed_at = Some(time);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerMode {
+    #[default]
+    Simple,
+    Pomodoro,
+}
+
+/// Timer start options, pomodoro lengths are in minutes
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TimerOptions {
+    #[param(inline)]
+    pub mode: Option<TimerMode>,
+    #[param(inline)]
+    pub work: Option<i64>,
+    #[param(inline)]
+    pub short_break: Option<i64>,
+    #[param(inline)]
+    pub long_break: Option<i64>,
+    #[param(inline)]
+    pub long_break_interval: Option<i64>,
+}
+
+impl TimerOptions {
+    /// Longest pomodoro phase, in minutes
+    pub const MAX_PHASE: i64 = 24 * 60;
+    /// Most work phases before a long break
+    pub const MAX_LONG_BREAK_INTERVAL: i64 = 100;
+
+    /// Phases should last from a minute to `MAX_PHASE` minutes, lengths are kept in seconds
+    pub fn pomodoro(&self) -> Result<Option<Pomodoro>, handle_errors::Error> {
+        if self.mode != Some(TimerMode::Pomodoro) {
+            return Ok(None);
+        }
+        let phase = |minutes: Option<i64>, default: i64| match minutes {
+            Some(minutes) if (1..=Self::MAX_PHASE).contains(&minutes) => Ok(minutes * 60),
+            Some(_) => Err(handle_errors::Error::InvalidPomodoro),
+            None => Ok(default),
+        };
+        let default = Pomodoro::default();
+        let pomodoro = Pomodoro {
+            work: phase(self.work, default.work)?,
+            short_break: phase(self.short_break, default.short_break)?,
+            long_break: phase(self.long_break, default.long_break)?,
+            long_break_interval: self
+                .long_break_interval
+                .unwrap_or(default.long_break_interval),
+        };
+        if !(1..=Self::MAX_LONG_BREAK_INTERVAL).contains(&pomodoro.long_break_interval) {
+            return Err(handle_errors::Error::InvalidPomodoro);
+        }
+        Ok(Some(pomodoro))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PomodoroPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+/// Pomodoro cycle lengths in seconds, a long break follows every `long_break_interval` work phase
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct Pomodoro {
+    pub work: i64,
+    pub short_break: i64,
+    pub long_break: i64,
+    pub long_break_interval: i64,
+}
+
+impl Default for Pomodoro {
+    fn default() -> Self {
+        Pomodoro {
+            work: 25 * 60,
+            short_break: 5 * 60,
+            long_break: 15 * 60,
+            long_break_interval: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PomodoroProgress {
+    pub phase: PomodoroPhase,
+    /// Number of the current or the last finished work phase, starts with 1
+    pub cycle: i64,
+    pub phase_remaining: i64,
+    pub worked: i64,
+}
+
+impl Pomodoro {
+    pub fn progress(&self, elapsed: i64) -> PomodoroProgress {
+        let set = self.long_break_interval * (self.work + self.short_break) - self.short_break
+            + self.long_break;
+        let sets = elapsed / set;
+        let mut left = elapsed % set;
+        let mut cycle = sets * self.long_break_interval;
+
+        for i in 0..self.long_break_interval {
+            cycle += 1;
+            if left < self.work {
+                return PomodoroProgress {
+                    phase: PomodoroPhase::Work,
+                    cycle,
+                    phase_remaining: self.work - left,
+                    worked: (cycle - 1) * self.work + left,
+                };
+            }
+            left -= self.work;
+            if i == self.long_break_interval - 1 {
+                break;
+            }
+            if left < self.short_break {
+                return PomodoroProgress {
+                    phase: PomodoroPhase::ShortBreak,
+                    cycle,
+                    phase_remaining: self.short_break - left,
+                    worked: cycle * self.work,
+                };
+            }
+            left -= self.short_break;
+        }
+        // the rest of the set is the long break
+        PomodoroProgress {
+            phase: PomodoroPhase::LongBreak,
+            cycle,
+            phase_remaining: (self.long_break - left).max(0),
+            worked: cycle * self.work,
+        }
+    }
+}
+
+/// Pomodoro settings with the phase and cycle kept in the cache
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct PomodoroState {
+    pub settings: Pomodoro,
+    pub phase: PomodoroPhase,
+    pub cycle: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct PomodoroStatus {
+    pub phase: PomodoroPhase,
+    pub cycle: i64,
+    pub phase_remaining: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TimerPhase {
+    Running,
+    Paused,
+    Stopped,
+}
+
+/// Timer answer for clients, budget counts the running session, all in seconds.
+/// In pomodoro mode `elapsed` counts only the work phases.
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct TimerStatus {
+    pub activity_id: ActivityId,
+    pub state: TimerPhase,
+    pub started_at: Option<DateTime<Utc>>,
+    pub elapsed: i64,
+    pub remaining: i64,
+    pub overtime: i64,
+    pub pomodoro: Option<PomodoroStatus>,
+}
+
+impl TimerStatus {
+    pub fn new(activity: &Activity, timer: Option<&TimerState>, time: DateTime<Utc>) -> Self {
+        let (state, started_at, elapsed) = match timer {
+            Some(timer) if timer.is_paused() => (
+                TimerPhase::Paused,
+                Some(timer.started_at),
+                timer.worked(time),
+            ),
+            Some(timer) => (
+                TimerPhase::Running,
+                Some(timer.started_at),
+                timer.worked(time),
+            ),
+            None => (TimerPhase::Stopped, None, 0),
+        };
+        let pomodoro = timer.and_then(|timer| {
+            let progress = timer
+                .pomodoro
+                .as_ref()?
+                .settings
+                .progress(timer.elapsed(time));
+            Some(PomodoroStatus {
+                phase: progress.phase,
+                cycle: progress.cycle,
+                phase_remaining: progress.phase_remaining,
+            })
+        });
+        let left = activity.time as i64 - activity.spent as i64 - elapsed;
+        TimerStatus {
+            activity_id: activity.id.clone(),
+            state,
+            started_at,
+            elapsed,
+            remaining: left.max(0),
+            overtime: (-left).max(0),
+            pomodoro,
+        }
+    }
+}
+
+/// Answer of timer start, `stopped` holds sessions closed by the single active timer mode
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct StartedTimer {
+    pub timer: TimerStatus,
+    pub stopped: Vec<TimeEntry>,
+}
+
+#[cfg(test)]
+mod test_timer_state {
+    use super::*;
+    use crate::tests::helpers::test_activity;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn small_test_elapsed_of_running_timer() {
+        let start = Utc::now();
+        let timer = TimerState::new(start);
+        assert_eq!(timer.elapsed(start + TimeDelta::seconds(90)), 90);
+    }
+
+    #[test]
+    fn small_test_paused_time_is_not_counted() {
+        let start = Utc::now();
+        let mut timer = TimerState::new(start);
+        timer.pause(start + TimeDelta::seconds(60));
+        assert!(timer.is_paused());
+        assert_eq!(timer.elapsed(start + TimeDelta::seconds(600)), 60);
+        timer.resume(start + TimeDelta::seconds(600));
+        assert_eq!(timer.elapsed(start + TimeDelta::seconds(630)), 90);
+        assert_eq!(timer.started_at, start);
+    }
+
+    #[test]
+    fn small_test_status_counts_remaining_budget() {
+        let start = Utc::now();
+        let activity = test_activity(1, 60, 10);
+        let mut timer = TimerState::new(start);
+        timer.pause(start + TimeDelta::seconds(20));
+
+        let status = TimerStatus::new(&activity, Some(&timer), start + TimeDelta::seconds(30));
+        assert_eq!(status.state, TimerPhase::Paused);
+        assert_eq!(status.elapsed, 20);
+        assert_eq!(status.remaining, 30);
+        assert_eq!(status.overtime, 0);
+
+        let status = TimerStatus::new(&activity, None, start);
+        assert_eq!(status.state, TimerPhase::Stopped);
+        assert_eq!(status.remaining, 50);
+    }
+
+    #[test]
+    fn small_test_status_shows_overtime_of_running_timer() {
+        let start = Utc::now();
+        let activity = test_activity(1, 60, 50);
+        let timer = TimerState::new(start);
+
+        let status = TimerStatus::new(&activity, Some(&timer), start + TimeDelta::seconds(25));
+        assert_eq!(status.state, TimerPhase::Running);
+        assert_eq!(status.remaining, 0);
+        assert_eq!(status.overtime, 15);
+    }
+
+    #[test]
+    fn small_test_pomodoro_cycles_work_and_breaks() {
+        let pomodoro = Pomodoro {
+            work: 25,
+            short_break: 5,
+            long_break: 15,
+            long_break_interval: 2,
+        };
+        let progress = pomodoro.progress(10);
+        assert_eq!(progress.phase, PomodoroPhase::Work);
+        assert_eq!(progress.cycle, 1);
+        assert_eq!(progress.phase_remaining, 15);
+        assert_eq!(progress.worked, 10);
+
+        let progress = pomodoro.progress(27);
+        assert_eq!(progress.phase, PomodoroPhase::ShortBreak);
+        assert_eq!(progress.cycle, 1);
+        assert_eq!(progress.phase_remaining, 3);
+        assert_eq!(progress.worked, 25);
+
+        let progress = pomodoro.progress(60);
+        assert_eq!(progress.phase, PomodoroPhase::LongBreak);
+        assert_eq!(progress.cycle, 2);
+        assert_eq!(progress.worked, 50);
+
+        let progress = pomodoro.progress(72);
+        assert_eq!(progress.phase, PomodoroPhase::Work);
+        assert_eq!(progress.cycle, 3);
+        assert_eq!(progress.worked, 52);
+    }
+
+    #[test]
+    fn small_test_pomodoro_timer_books_only_work() {
+        let start = Utc::now();
+        let mut timer = TimerState::with_pomodoro(start, Pomodoro::default());
+        let time = start + TimeDelta::minutes(35);
+        assert_eq!(timer.elapsed(time), 35 * 60);
+        assert_eq!(timer.worked(time), 30 * 60);
+        assert!(timer.sync(time));
+        let pomodoro = timer.pomodoro.as_ref().unwrap();
+        assert_eq!(pomodoro.phase, PomodoroPhase::Work);
+        assert_eq!(pomodoro.cycle, 2);
+        assert!(!timer.sync(time));
+    }
+
+    #[test]
+    fn small_test_pomodoro_options_are_minutes() {
+        let options = TimerOptions {
+            mode: Some(TimerMode::Pomodoro),
+            work: Some(50),
+            ..Default::default()
+        };
+        let pomodoro = options.pomodoro().unwrap().unwrap();
+        assert_eq!(pomodoro.work, 50 * 60);
+        assert_eq!(pomodoro.short_break, 5 * 60);
+        assert!(TimerOptions::default().pomodoro().unwrap().is_none());
+
+        let options = TimerOptions {
+            mode: Some(TimerMode::Pomodoro),
+            long_break_interval: Some(0),
+            ..Default::default()
+        };
+        assert!(options.pomodoro().is_err());
+    }
+
+    #[test]
+    fn small_test_pomodoro_options_are_bounded() {
+        let options = TimerOptions {
+            mode: Some(TimerMode::Pomodoro),
+            work: Some(i64::MAX),
+            ..Default::default()
+        };
+        assert!(options.pomodoro().is_err());
+        let options = TimerOptions {
+            mode: Some(TimerMode::Pomodoro),
+            long_break: Some(TimerOptions::MAX_PHASE + 1),
+            ..Default::default()
+        };
+        assert!(options.pomodoro().is_err());
+        let options = TimerOptions {
+            mode: Some(TimerMode::Pomodoro),
+            long_break_interval: Some(i64::MAX),
+            ..Default::default()
+        };
+        assert!(options.pomodoro().is_err());
+
+        let options = TimerOptions {
+            mode: Some(TimerMode::Pomodoro),
+            work: Some(TimerOptions::MAX_PHASE),
+            short_break: Some(TimerOptions::MAX_PHASE),
+            long_break: Some(TimerOptions::MAX_PHASE),
+            long_break_interval: Some(TimerOptions::MAX_LONG_BREAK_INTERVAL),
+        };
+        let pomodoro = options.pomodoro().unwrap().unwrap();
+        let progress = pomodoro.progress(i64::MAX / 2);
+        assert!(progress.phase_remaining > 0);
+    }
+}
@@ -0,0 +1,12 @@
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::types::activities::Activity;
+
+/// Deleted activity, it can be restored until the trash is purged
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct TrashedActivity {
+    pub activity: Activity,
+    pub deleted_at: DateTime<Utc>,
+}